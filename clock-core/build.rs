@@ -0,0 +1,10 @@
+/// the ntp era pivot, `SOURCE_DATE_EPOCH` when set so the same sources build the same binary
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let build_time = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.trim().parse::<u64>().expect("SOURCE_DATE_EPOCH is not unix seconds"),
+        Err(_) => std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+    };
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_time);
+}
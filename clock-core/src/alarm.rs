@@ -0,0 +1,257 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::tz::Zone;
+
+/// how long a double click puts a ringing alarm off
+const SNOOZE_MINUTES: i64 = 9;
+/// the clock stepping further than this between two polls is a sync or a restart,
+/// alarms passed over are dropped instead of all ringing at once
+const MAX_CATCH_UP_MINUTES: i64 = 5;
+const WEEKDAYS: [char; 7] = ['M', 'T', 'W', 'T', 'F', 'S', 'S'];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Alarm {
+    pub hour: u32,
+    pub minute: u32,
+    /// bit 0 for Monday through bit 6 for Sunday, with none the alarm rings once and turns itself off
+    #[serde(default)]
+    pub repeat: u8,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+const fn default_enabled() -> bool { true }
+
+impl Alarm {
+    pub fn time(&self) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(self.hour, self.minute, 0)
+    }
+
+    fn rings_on(&self, date: NaiveDate) -> bool {
+        self.repeat == 0 || self.repeat & (1 << date.weekday().num_days_from_monday()) != 0
+    }
+
+    /// `MTWTF--` for the days it repeats on, `once` without any
+    pub fn repeat_days(&self) -> String {
+        match self.repeat {
+            0 => String::from("once"),
+            repeat => WEEKDAYS
+                .iter()
+                .enumerate()
+                .map(|(day, name)| if repeat & (1 << day) != 0 { *name } else { '-' })
+                .collect(),
+        }
+    }
+
+    /// first time it rings after `after` on the wall clock of `zone`, whether enabled or not
+    pub fn next_after(&self, after: &DateTime<Utc>, zone: &Zone) -> Option<DateTime<Utc>> {
+        let time = self.time()?;
+        let today = zone.to_local(after).date_naive();
+        // from yesterday, a DST jump can move a late alarm of the day before past midnight
+        (-1..=7)
+            .map(|days| today + Duration::days(days))
+            .filter(|date| self.rings_on(*date))
+            .map(|date| zone.from_local(&date.and_time(time)))
+            .find(|at| at > after)
+    }
+}
+
+/// told about every change to the alarms, keeps them in flash
+pub type SaveAlarms = Box<dyn FnMut(&[Alarm])>;
+
+/// rings the alarms, the time and zone come from the caller so nothing here reads the system clock
+pub struct Scheduler {
+    alarms: Vec<Alarm>,
+    save: SaveAlarms,
+    /// time of the last poll, alarms due since then ring on the next
+    checked: Option<DateTime<Utc>>,
+    ringing: Option<usize>,
+    snoozed: Option<(usize, DateTime<Utc>)>,
+}
+
+impl Scheduler {
+    pub fn new(alarms: Vec<Alarm>, save: SaveAlarms) -> Self {
+        Self { alarms, save, checked: None, ringing: None, snoozed: None }
+    }
+
+    fn save(&mut self) {
+        (self.save)(&self.alarms);
+    }
+
+    pub fn alarms(&self) -> &[Alarm] {
+        &self.alarms
+    }
+
+    pub fn ringing(&self) -> Option<&Alarm> {
+        self.ringing.map(|index| &self.alarms[index])
+    }
+
+    pub fn toggle(&mut self, index: usize) {
+        if let Some(alarm) = self.alarms.get_mut(index) {
+            alarm.enabled = !alarm.enabled;
+            self.save();
+        }
+    }
+
+    /// the alarm that starts ringing at `now`, one rings at a time and those due meanwhile are dropped
+    pub fn poll(&mut self, now: &DateTime<Utc>, zone: &Zone) -> Option<&Alarm> {
+        let last = match self.checked.replace(*now) {
+            Some(last) if last <= *now && *now - last <= Duration::minutes(MAX_CATCH_UP_MINUTES) => last,
+            _ => return None,
+        };
+        if self.ringing.is_some() {
+            return None;
+        }
+        let index = match self.snoozed {
+            Some((index, until)) if until <= *now => {
+                self.snoozed = None;
+                index
+            }
+            _ => self.alarms
+                .iter()
+                .position(|alarm| alarm.enabled && alarm.next_after(&last, zone).is_some_and(|at| at <= *now))?,
+        };
+        if self.alarms[index].repeat == 0 && self.alarms[index].enabled {
+            self.alarms[index].enabled = false;
+            self.save();
+        }
+        self.ringing = Some(index);
+        self.ringing()
+    }
+
+    pub fn dismiss(&mut self) {
+        self.ringing = None;
+    }
+
+    pub fn snooze(&mut self, now: &DateTime<Utc>) {
+        if let Some(index) = self.ringing.take() {
+            self.snoozed = Some((index, *now + Duration::minutes(SNOOZE_MINUTES)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, s).unwrap().and_utc()
+    }
+
+    fn alarm(hour: u32, minute: u32, repeat: u8) -> Alarm {
+        Alarm { hour, minute, repeat, label: String::new(), enabled: true }
+    }
+
+    fn berlin() -> Zone {
+        Zone::parse("Europe/Berlin").unwrap()
+    }
+
+    /// a scheduler and every list of alarms it saved
+    fn scheduler(alarms: Vec<Alarm>) -> (Scheduler, Rc<RefCell<Vec<Vec<Alarm>>>>) {
+        let saved = Rc::new(RefCell::new(Vec::new()));
+        let log = saved.clone();
+        (Scheduler::new(alarms, Box::new(move |alarms: &[Alarm]| log.borrow_mut().push(alarms.to_vec()))), saved)
+    }
+
+    /// polls once a minute from `from` to `to` and dismisses whatever rings, the times it rang
+    fn ring_times(scheduler: &mut Scheduler, zone: &Zone, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let mut rang = Vec::new();
+        let mut now = from;
+        while now <= to {
+            if scheduler.poll(&now, zone).is_some() {
+                rang.push(now);
+                scheduler.dismiss();
+            }
+            now += Duration::minutes(1);
+        }
+        rang
+    }
+
+    #[test]
+    fn spring_forward_gap() {
+        // 02:30 does not exist on 2024-03-31 in Berlin, it rings at 03:30 CEST
+        let (mut scheduler, _) = scheduler(vec![alarm(2, 30, 0x7f)]);
+        let rang = ring_times(&mut scheduler, &berlin(), utc(2024, 3, 30, 23, 0, 0), utc(2024, 3, 31, 3, 0, 0));
+        assert_eq!(rang, vec![utc(2024, 3, 31, 1, 30, 0)]);
+    }
+
+    #[test]
+    fn fall_back_rings_once() {
+        // 02:30 comes twice on 2024-10-27 in Berlin, only the first one rings
+        let (mut scheduler, _) = scheduler(vec![alarm(2, 30, 0x7f)]);
+        let rang = ring_times(&mut scheduler, &berlin(), utc(2024, 10, 26, 23, 0, 0), utc(2024, 10, 27, 3, 0, 0));
+        assert_eq!(rang, vec![utc(2024, 10, 27, 0, 30, 0)]);
+    }
+
+    #[test]
+    fn across_midnight() {
+        // 23:59 CET is 22:59 UTC, the polls either side of it are on different days
+        let zone = berlin();
+        let (mut scheduler, _) = scheduler(vec![alarm(23, 59, 0x7f)]);
+        assert!(scheduler.poll(&utc(2024, 1, 1, 22, 58, 30), &zone).is_none());
+        assert!(scheduler.poll(&utc(2024, 1, 1, 23, 0, 10), &zone).is_some());
+        scheduler.dismiss();
+        let rang = ring_times(&mut scheduler, &zone, utc(2024, 1, 1, 23, 1, 10), utc(2024, 1, 2, 23, 1, 10));
+        assert_eq!(rang, vec![utc(2024, 1, 2, 22, 59, 10)]);
+    }
+
+    #[test]
+    fn repeat_days() {
+        // Monday and Wednesday, 2024-01-01 is a Monday
+        let zone = Zone::fixed(0);
+        let (mut scheduler, _) = scheduler(vec![alarm(7, 0, 0b101)]);
+        let rang = ring_times(&mut scheduler, &zone, utc(2024, 1, 1, 0, 0, 0), utc(2024, 1, 7, 23, 59, 0));
+        assert_eq!(rang, vec![utc(2024, 1, 1, 7, 0, 0), utc(2024, 1, 3, 7, 0, 0)]);
+        assert_eq!(scheduler.alarms()[0].repeat_days(), "M-W----");
+    }
+
+    #[test]
+    fn snooze_expires() {
+        let zone = Zone::fixed(0);
+        let (mut scheduler, _) = scheduler(vec![alarm(7, 0, 0x7f)]);
+        scheduler.poll(&utc(2024, 1, 1, 6, 59, 30), &zone);
+        assert!(scheduler.poll(&utc(2024, 1, 1, 7, 0, 0), &zone).is_some());
+        scheduler.snooze(&utc(2024, 1, 1, 7, 0, 20));
+        assert!(scheduler.ringing().is_none());
+        let rang = ring_times(&mut scheduler, &zone, utc(2024, 1, 1, 7, 0, 30), utc(2024, 1, 1, 8, 0, 30));
+        assert_eq!(rang, vec![utc(2024, 1, 1, 7, 9, 30)]);
+    }
+
+    #[test]
+    fn one_off_turns_itself_off() {
+        let zone = Zone::fixed(0);
+        let (mut scheduler, saved) = scheduler(vec![alarm(7, 0, 0)]);
+        let rang = ring_times(&mut scheduler, &zone, utc(2024, 1, 1, 6, 0, 0), utc(2024, 1, 3, 8, 0, 0));
+        assert_eq!(rang, vec![utc(2024, 1, 1, 7, 0, 0)]);
+        assert!(!scheduler.alarms()[0].enabled);
+        assert_eq!(saved.borrow().len(), 1);
+        assert!(!saved.borrow()[0][0].enabled);
+        scheduler.toggle(0);
+        assert!(saved.borrow()[1][0].enabled);
+    }
+
+    #[test]
+    fn clock_jumps_drop_the_alarms_passed_over() {
+        let zone = Zone::fixed(0);
+        let (mut scheduler, _) = scheduler(vec![alarm(7, 0, 0x7f)]);
+        // the first poll has nothing to compare with
+        assert!(scheduler.poll(&utc(2024, 1, 1, 7, 0, 0), &zone).is_none());
+        // polls up to MAX_CATCH_UP_MINUTES apart still catch it
+        scheduler.poll(&utc(2024, 1, 2, 6, 56, 0), &zone);
+        assert!(scheduler.poll(&(utc(2024, 1, 2, 6, 56, 0) + Duration::minutes(MAX_CATCH_UP_MINUTES)), &zone).is_some());
+        scheduler.dismiss();
+        // a sync moving the clock on by more than that drops it
+        scheduler.poll(&utc(2024, 1, 3, 6, 50, 0), &zone);
+        assert!(scheduler.poll(&utc(2024, 1, 3, 7, 1, 0), &zone).is_none());
+        assert!(scheduler.poll(&utc(2024, 1, 3, 7, 2, 0), &zone).is_none());
+        // and back in time
+        scheduler.poll(&utc(2024, 1, 4, 7, 1, 0), &zone);
+        assert!(scheduler.poll(&utc(2024, 1, 4, 6, 59, 0), &zone).is_none());
+        assert!(scheduler.poll(&utc(2024, 1, 4, 7, 0, 0), &zone).is_some());
+    }
+}
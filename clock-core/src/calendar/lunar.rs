@@ -0,0 +1,237 @@
+use std::fmt::{Display, Formatter};
+
+use chrono::{Duration, NaiveDate};
+
+pub const FIRST_YEAR: i32 = 1900;
+pub const LAST_YEAR: i32 = 2100;
+
+/// one word per lunar year: bits 15..4 set for the 30 day months 1 to 12,
+/// bits 3..0 the leap month (0 for none) and bit 16 set when it has 30 days
+const LUNAR_INFO: [u32; (LAST_YEAR - FIRST_YEAR + 1) as usize] = [
+    0x04bd8, 0x04ae0, 0x0a570, 0x054d5, 0x0d260, 0x0d950, 0x16554, 0x056a0, 0x09ad0, 0x055d2, // 1900
+    0x04ae0, 0x0a5b6, 0x0a4d0, 0x0d250, 0x1d255, 0x0b540, 0x0d6a0, 0x0ada2, 0x095b0, 0x14977, // 1910
+    0x04970, 0x0a4b0, 0x0b4b5, 0x06a50, 0x06d40, 0x1ab54, 0x02b60, 0x09570, 0x052f2, 0x04970, // 1920
+    0x06566, 0x0d4a0, 0x0ea50, 0x16a95, 0x05ad0, 0x02b60, 0x186e3, 0x092e0, 0x1c8d7, 0x0c950, // 1930
+    0x0d4a0, 0x1d8a6, 0x0b550, 0x056a0, 0x1a5b4, 0x025d0, 0x092d0, 0x0d2b2, 0x0a950, 0x0b557, // 1940
+    0x06ca0, 0x0b550, 0x15355, 0x04da0, 0x0a5b0, 0x14573, 0x052b0, 0x0a9a8, 0x0e950, 0x06aa0, // 1950
+    0x0aea6, 0x0ab50, 0x04b60, 0x0aae4, 0x0a570, 0x05260, 0x0f263, 0x0d950, 0x05b57, 0x056a0, // 1960
+    0x096d0, 0x04dd5, 0x04ad0, 0x0a4d0, 0x0d4d4, 0x0d250, 0x0d558, 0x0b540, 0x0b6a0, 0x195a6, // 1970
+    0x095b0, 0x049b0, 0x0a974, 0x0a4b0, 0x0b27a, 0x06a50, 0x06d40, 0x0af46, 0x0ab60, 0x09570, // 1980
+    0x04af5, 0x04970, 0x064b0, 0x074a3, 0x0ea50, 0x06b58, 0x05ac0, 0x0ab60, 0x096d5, 0x092e0, // 1990
+    0x0c960, 0x0d954, 0x0d4a0, 0x0da50, 0x07552, 0x056a0, 0x0abb7, 0x025d0, 0x092d0, 0x0cab5, // 2000
+    0x0a950, 0x0b4a0, 0x0baa4, 0x0ad50, 0x055d9, 0x04ba0, 0x0a5b0, 0x15176, 0x052b0, 0x0a930, // 2010
+    0x07954, 0x06aa0, 0x0ad50, 0x05b52, 0x04b60, 0x0a6e6, 0x0a4e0, 0x0d260, 0x0ea65, 0x0d530, // 2020
+    0x05aa0, 0x076a3, 0x096d0, 0x04afb, 0x04ad0, 0x0a4d0, 0x1d0b6, 0x0d250, 0x0d520, 0x0dd45, // 2030
+    0x0b5a0, 0x056d0, 0x055b2, 0x049b0, 0x0a577, 0x0a4b0, 0x0aa50, 0x1b255, 0x06d20, 0x0ada0, // 2040
+    0x14b63, 0x09370, 0x049f8, 0x04970, 0x064b0, 0x168a6, 0x0ea50, 0x06b20, 0x1a6c4, 0x0aae0, // 2050
+    0x092e0, 0x0d2e3, 0x0c960, 0x0d557, 0x0d4a0, 0x0da50, 0x05d55, 0x056a0, 0x0a6d0, 0x055d4, // 2060
+    0x052d0, 0x0a9b8, 0x0a950, 0x0b4a0, 0x0b6a6, 0x0ad50, 0x055a0, 0x0aba4, 0x0a5b0, 0x052b0, // 2070
+    0x0b273, 0x06930, 0x07337, 0x06aa0, 0x0ad50, 0x14b55, 0x04b60, 0x0a570, 0x054e4, 0x0d160, // 2080
+    0x0e968, 0x0d520, 0x0daa0, 0x16aa6, 0x056d0, 0x04ae0, 0x0a9d4, 0x0a2d0, 0x0d150, 0x0f252, // 2090
+    0x0d520, // 2100
+];
+
+const MONTHS: [&str; 12] = ["正", "二", "三", "四", "五", "六", "七", "八", "九", "十", "冬", "腊"];
+const DIGITS: [&str; 10] = ["十", "一", "二", "三", "四", "五", "六", "七", "八", "九"];
+const DECADES: [&str; 4] = ["初", "十", "廿", "三"];
+const STEMS: [&str; 10] = ["甲", "乙", "丙", "丁", "戊", "己", "庚", "辛", "壬", "癸"];
+const BRANCHES: [&str; 12] = ["子", "丑", "寅", "卯", "辰", "巳", "午", "未", "申", "酉", "戌", "亥"];
+const ZODIAC: [&str; 12] = ["鼠", "牛", "虎", "兔", "龙", "蛇", "马", "羊", "猴", "鸡", "狗", "猪"];
+
+/// gregorian date of lunar 1900-01-01
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1900, 1, 31).unwrap()
+}
+
+fn info(year: i32) -> u32 {
+    LUNAR_INFO[(year - FIRST_YEAR) as usize]
+}
+
+/// 0 when the year has no leap month
+pub fn leap_month(year: i32) -> u32 {
+    info(year) & 0xf
+}
+
+fn leap_days(year: i32) -> i64 {
+    match (leap_month(year), info(year) & 0x10000) {
+        (0, _) => 0,
+        (_, 0) => 29,
+        _ => 30,
+    }
+}
+
+pub fn month_days(year: i32, month: u32) -> i64 {
+    match info(year) & (0x10000 >> month) {
+        0 => 29,
+        _ => 30,
+    }
+}
+
+fn year_days(year: i32) -> i64 {
+    (1..=12).map(|month| month_days(year, month)).sum::<i64>() + leap_days(year)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LunarDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    /// the leap month repeating `month`
+    pub leap: bool,
+}
+
+impl LunarDate {
+    /// gregorian 1900-01-31 to 2101-01-28
+    pub fn from_solar(date: NaiveDate) -> Option<Self> {
+        let mut offset = (date - epoch()).num_days();
+        if offset < 0 {
+            return None;
+        }
+        let mut year = FIRST_YEAR;
+        while offset >= year_days(year) {
+            offset -= year_days(year);
+            year += 1;
+            if year > LAST_YEAR {
+                return None;
+            }
+        }
+        let leap_month = leap_month(year);
+        for month in 1..=12 {
+            let days = month_days(year, month);
+            if offset < days {
+                return Some(Self { year, month, day: offset as u32 + 1, leap: false });
+            }
+            offset -= days;
+            if month == leap_month {
+                let days = leap_days(year);
+                if offset < days {
+                    return Some(Self { year, month, day: offset as u32 + 1, leap: true });
+                }
+                offset -= days;
+            }
+        }
+        None
+    }
+
+    pub fn to_solar(&self) -> Option<NaiveDate> {
+        if !(FIRST_YEAR..=LAST_YEAR).contains(&self.year) || !(1..=12).contains(&self.month)
+            || (self.leap && leap_month(self.year) != self.month) {
+            return None;
+        }
+        let month_length = match self.leap {
+            true => leap_days(self.year),
+            false => month_days(self.year, self.month),
+        };
+        if self.day == 0 || self.day as i64 > month_length {
+            return None;
+        }
+        let mut days = (FIRST_YEAR..self.year).map(year_days).sum::<i64>();
+        for month in 1..self.month {
+            days += month_days(self.year, month);
+            if month == leap_month(self.year) {
+                days += leap_days(self.year);
+            }
+        }
+        if self.leap {
+            days += month_days(self.year, self.month);
+        }
+        Some(epoch() + Duration::days(days + self.day as i64 - 1))
+    }
+
+    /// `九月`, `闰二月`
+    pub fn month_name(&self) -> String {
+        let name = MONTHS[self.month as usize - 1];
+        match self.leap {
+            true => format!("闰{}月", name),
+            false => format!("{}月", name),
+        }
+    }
+
+    /// `初七`, `廿三`
+    pub fn day_name(&self) -> String {
+        match self.day {
+            10 => String::from("初十"),
+            20 => String::from("二十"),
+            30 => String::from("三十"),
+            day => format!("{}{}", DECADES[day as usize / 10], DIGITS[day as usize % 10]),
+        }
+    }
+
+    /// sexagenary name of the year, `丙午`
+    pub fn year_name(&self) -> String {
+        let cycle = (self.year - 4).rem_euclid(60) as usize;
+        format!("{}{}", STEMS[cycle % 10], BRANCHES[cycle % 12])
+    }
+
+    pub fn zodiac(&self) -> &'static str {
+        ZODIAC[(self.year - 4).rem_euclid(12) as usize]
+    }
+
+    pub fn is_first_day_of_year(&self) -> bool {
+        self.month == 1 && self.day == 1 && !self.leap
+    }
+}
+
+impl Display for LunarDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.month_name(), self.day_name())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solar(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn lunar(year: i32, month: u32, day: u32, leap: bool) -> LunarDate {
+        LunarDate { year, month, day, leap }
+    }
+
+    #[test]
+    fn new_year() {
+        for (year, date) in [(1900, solar(1900, 1, 31)), (2023, solar(2023, 1, 22)), (2024, solar(2024, 2, 10)),
+                             (2100, solar(2100, 2, 9))] {
+            let new_year = lunar(year, 1, 1, false);
+            assert_eq!(LunarDate::from_solar(date), Some(new_year), "{}", year);
+            assert_eq!(new_year.to_solar(), Some(date));
+            assert!(new_year.is_first_day_of_year());
+            assert_eq!(LunarDate::from_solar(date.pred_opt().unwrap()).map(|d| d.year), (year > FIRST_YEAR).then_some(year - 1));
+        }
+    }
+
+    #[test]
+    fn leap_month_2023() {
+        // 闰二月 runs from 2023-03-22 to 2023-04-19
+        assert_eq!(leap_month(2023), 2);
+        let cases = [
+            (solar(2023, 3, 21), lunar(2023, 2, 30, false)),
+            (solar(2023, 3, 22), lunar(2023, 2, 1, true)),
+            (solar(2023, 4, 19), lunar(2023, 2, 29, true)),
+            (solar(2023, 4, 20), lunar(2023, 3, 1, false)),
+        ];
+        for (date, expected) in cases {
+            assert_eq!(LunarDate::from_solar(date), Some(expected), "{}", date);
+            assert_eq!(expected.to_solar(), Some(date));
+        }
+        assert_eq!(lunar(2023, 2, 1, true).month_name(), "闰二月");
+        assert_eq!(lunar(2024, 2, 1, true).to_solar(), None);
+        assert_eq!(leap_month(2024), 0);
+    }
+
+    #[test]
+    fn range_and_names() {
+        assert_eq!(LunarDate::from_solar(solar(1900, 1, 30)), None);
+        assert_eq!(LunarDate::from_solar(solar(2101, 1, 28)).map(|d| d.year), Some(2100));
+        assert_eq!(LunarDate::from_solar(solar(2101, 1, 29)), None);
+        // mid-autumn festival
+        let date = LunarDate::from_solar(solar(2024, 9, 17)).unwrap();
+        assert_eq!((date.year_name(), date.zodiac()), (String::from("甲辰"), "龙"));
+        assert_eq!(date.to_string(), "八月十五");
+        assert_eq!(lunar(2026, 1, 20, false).day_name(), "二十");
+        assert_eq!(lunar(2026, 1, 23, false).day_name(), "廿三");
+    }
+}
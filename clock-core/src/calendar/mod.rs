@@ -0,0 +1,31 @@
+use chrono::NaiveDate;
+
+use crate::calendar::lunar::LunarDate;
+use crate::calendar::terms::next_term;
+
+pub mod lunar;
+pub mod terms;
+
+/// `九月初七 寒露` on the day of a solar term, `九月初七 霜降5天` before the next one
+pub fn lunar_line(date: NaiveDate) -> Option<String> {
+    let lunar = LunarDate::from_solar(date)?;
+    Some(match next_term(date) {
+        None => lunar.to_string(),
+        Some(term) => match (term.date - date).num_days() {
+            0 => format!("{} {}", lunar, term.name()),
+            days => format!("{} {}{}天", lunar, term.name(), days),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line() {
+        let date = NaiveDate::from_ymd_opt(2024, 4, 4).unwrap();
+        assert_eq!(lunar_line(date).unwrap(), "二月廿六 清明");
+        assert_eq!(lunar_line(date.pred_opt().unwrap()).unwrap(), "二月廿五 清明1天");
+    }
+}
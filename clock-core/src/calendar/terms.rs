@@ -0,0 +1,284 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
+
+/// the 24 terms of a gregorian year, 小寒 at 285° of solar longitude first
+pub const NAMES: [&str; 24] = [
+    "小寒", "大寒", "立春", "雨水", "惊蛰", "春分", "清明", "谷雨", "立夏", "小满", "芒种", "夏至",
+    "小暑", "大暑", "立秋", "处暑", "白露", "秋分", "寒露", "霜降", "立冬", "小雪", "大雪", "冬至",
+];
+
+const J2000: f64 = 2_451_545.0;
+const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+const TROPICAL_YEAR: f64 = 365.2422;
+/// terms are dated in China standard time, like the lunar table
+const CHINA_OFFSET: i32 = 8 * 3600;
+
+/// tt minus ut in seconds every ten years from 1900, linear in between
+const DELTA_T: [f64; 21] = [
+    -2.8, 10.4, 21.2, 24.1, 24.4, 29.1, 33.1, 40.2, 50.5, 56.9,
+    63.9, 66.7, 71.6, 77.6, 84.7, 93.0, 113.7, 135.0, 156.9, 179.5, 202.7,
+];
+
+// truncated VSOP87 series of the earth's heliocentric longitude (Meeus, appendix III),
+// amplitude in 1e-8 rad, phase and frequency per julian millennium.
+// good to a few arc seconds, the terms come out within a minute
+const L0: [(f64, f64, f64); 64] = [
+    (175347046.0, 0.0, 0.0),
+    (3341656.0, 4.6692568, 6283.07585),
+    (34894.0, 4.6261, 12566.1517),
+    (3497.0, 2.7441, 5753.3849),
+    (3418.0, 2.8289, 3.5231),
+    (3136.0, 3.6277, 77713.7715),
+    (2676.0, 4.4181, 7860.4194),
+    (2343.0, 6.1352, 3930.2097),
+    (1324.0, 0.7425, 11506.7698),
+    (1273.0, 2.0371, 529.691),
+    (1199.0, 1.1096, 1577.3435),
+    (990.0, 5.233, 5884.927),
+    (902.0, 2.045, 26.298),
+    (857.0, 3.508, 398.149),
+    (780.0, 1.179, 5223.694),
+    (753.0, 2.533, 5507.553),
+    (505.0, 4.583, 18849.228),
+    (492.0, 4.205, 775.523),
+    (357.0, 2.92, 0.067),
+    (317.0, 5.849, 11790.629),
+    (284.0, 1.899, 796.298),
+    (271.0, 0.315, 10977.079),
+    (243.0, 0.345, 5486.778),
+    (206.0, 4.806, 2544.314),
+    (205.0, 1.869, 5573.143),
+    (202.0, 2.458, 6069.777),
+    (156.0, 0.833, 213.299),
+    (132.0, 3.411, 2942.463),
+    (126.0, 1.083, 20.775),
+    (115.0, 0.645, 0.98),
+    (103.0, 0.636, 4694.003),
+    (102.0, 0.976, 15720.839),
+    (102.0, 4.267, 7.114),
+    (99.0, 6.21, 2146.17),
+    (98.0, 0.68, 155.42),
+    (86.0, 5.98, 161000.69),
+    (85.0, 1.3, 6275.96),
+    (85.0, 3.67, 71430.7),
+    (80.0, 1.81, 17260.15),
+    (79.0, 3.04, 12036.46),
+    (75.0, 1.76, 5088.63),
+    (74.0, 3.5, 3154.69),
+    (74.0, 4.68, 801.82),
+    (70.0, 0.83, 9437.76),
+    (62.0, 3.98, 8827.39),
+    (61.0, 1.82, 7084.9),
+    (57.0, 2.78, 6286.6),
+    (56.0, 4.39, 14143.5),
+    (56.0, 3.47, 6279.55),
+    (52.0, 0.19, 12139.55),
+    (52.0, 1.33, 1748.02),
+    (51.0, 0.28, 5856.48),
+    (49.0, 0.49, 1194.45),
+    (41.0, 5.37, 8429.24),
+    (41.0, 2.4, 19651.05),
+    (39.0, 6.17, 10447.39),
+    (37.0, 6.04, 10213.29),
+    (37.0, 2.57, 1059.38),
+    (36.0, 1.71, 2352.87),
+    (36.0, 1.78, 6812.77),
+    (33.0, 0.59, 17789.85),
+    (30.0, 0.44, 83996.85),
+    (30.0, 2.74, 1349.87),
+    (25.0, 3.16, 4690.48),
+];
+
+const L1: [(f64, f64, f64); 34] = [
+    (628331966747.0, 0.0, 0.0),
+    (206059.0, 2.678235, 6283.07585),
+    (4303.0, 2.6351, 12566.1517),
+    (425.0, 1.59, 3.523),
+    (119.0, 5.796, 26.298),
+    (109.0, 2.966, 1577.344),
+    (93.0, 2.59, 18849.23),
+    (72.0, 1.14, 529.69),
+    (68.0, 1.87, 398.15),
+    (67.0, 4.41, 5507.55),
+    (59.0, 2.89, 5223.69),
+    (56.0, 2.17, 155.42),
+    (45.0, 0.4, 796.3),
+    (36.0, 0.47, 775.52),
+    (29.0, 2.65, 7.11),
+    (21.0, 5.34, 0.98),
+    (19.0, 1.85, 5486.78),
+    (19.0, 4.97, 213.3),
+    (17.0, 2.99, 6275.96),
+    (16.0, 0.03, 2544.31),
+    (16.0, 1.43, 2146.17),
+    (15.0, 1.21, 10977.08),
+    (12.0, 2.83, 1748.02),
+    (12.0, 3.26, 5088.63),
+    (12.0, 5.27, 1194.45),
+    (12.0, 2.08, 4694.0),
+    (11.0, 0.77, 553.57),
+    (10.0, 1.3, 6286.6),
+    (10.0, 4.24, 1349.87),
+    (9.0, 2.7, 242.73),
+    (9.0, 5.64, 951.72),
+    (8.0, 5.3, 2352.87),
+    (6.0, 2.65, 9437.76),
+    (6.0, 4.67, 4690.48),
+];
+
+const L2: [(f64, f64, f64); 20] = [
+    (52919.0, 0.0, 0.0),
+    (8720.0, 1.0721, 6283.0758),
+    (309.0, 0.867, 12566.152),
+    (27.0, 0.05, 3.52),
+    (16.0, 5.19, 26.3),
+    (16.0, 3.68, 155.42),
+    (10.0, 0.76, 18849.23),
+    (9.0, 2.06, 77713.77),
+    (7.0, 0.83, 775.52),
+    (5.0, 4.66, 1577.34),
+    (4.0, 1.03, 7.11),
+    (4.0, 3.44, 5573.14),
+    (3.0, 5.14, 796.3),
+    (3.0, 6.05, 5507.55),
+    (3.0, 1.19, 242.73),
+    (3.0, 6.12, 529.69),
+    (3.0, 0.31, 398.15),
+    (3.0, 2.28, 553.57),
+    (2.0, 4.38, 5223.69),
+    (2.0, 3.75, 0.98),
+];
+
+const L3: [(f64, f64, f64); 7] = [
+    (289.0, 5.844, 6283.076),
+    (35.0, 0.0, 0.0),
+    (17.0, 5.49, 12566.15),
+    (3.0, 5.2, 155.42),
+    (1.0, 4.72, 3.52),
+    (1.0, 5.3, 18849.23),
+    (1.0, 5.97, 242.73),
+];
+
+// 3.142 is the rounded vsop87 phase, not pi
+#[allow(clippy::approx_constant)]
+const L4: [(f64, f64, f64); 3] = [
+    (114.0, 3.142, 0.0),
+    (8.0, 4.13, 6283.08),
+    (1.0, 3.84, 12566.15),
+];
+
+fn delta_t(year: f64) -> f64 {
+    let position = ((year - 1900.0) / 10.0).clamp(0.0, (DELTA_T.len() - 1) as f64);
+    let i = (position as usize).min(DELTA_T.len() - 2);
+    DELTA_T[i] + (DELTA_T[i + 1] - DELTA_T[i]) * (position - i as f64)
+}
+
+fn series(terms: &[(f64, f64, f64)], t: f64) -> f64 {
+    terms.iter().map(|(a, b, c)| a * (b + c * t).cos()).sum()
+}
+
+/// apparent geocentric longitude of the sun in degrees at julian ephemeris day `jde`
+fn sun_longitude(jde: f64) -> f64 {
+    let t = (jde - J2000) / 365_250.0;
+    let l = (series(&L0, t) + t * (series(&L1, t) + t * (series(&L2, t)
+        + t * (series(&L3, t) + t * series(&L4, t))))) / 1e8;
+    let centuries = t * 10.0;
+    let node = (125.04452 - 1934.136261 * centuries).to_radians();
+    let sun = (280.4665 + 36000.7698 * centuries).to_radians();
+    let moon = (218.3165 + 481267.8813 * centuries).to_radians();
+    // nutation in longitude, arc seconds
+    let nutation = -17.20 * node.sin() - 1.32 * (2.0 * sun).sin() - 0.23 * (2.0 * moon).sin() + 0.21 * (2.0 * node).sin();
+    // fk5 correction and aberration
+    let seconds = nutation - 0.09033 - 20.4898;
+    (l * 180.0 / PI + 180.0 + seconds / 3600.0).rem_euclid(360.0)
+}
+
+/// when the sun reaches term `index` (0 is 小寒) in gregorian `year`
+pub fn term_time(year: i32, index: usize) -> Option<DateTime<Utc>> {
+    let target = (285.0 + 15.0 * index as f64) % 360.0;
+    // 小寒 falls around January 5th, one term every 15.2 days
+    let mut jde = J2000 + (year - 2000) as f64 * TROPICAL_YEAR + 4.5 + index as f64 * TROPICAL_YEAR / 24.0;
+    for _ in 0..10 {
+        let delta = (target - sun_longitude(jde) + 180.0).rem_euclid(360.0) - 180.0;
+        jde += delta / 360.0 * TROPICAL_YEAR;
+        if delta.abs() < 1e-7 {
+            break;
+        }
+    }
+    let jd = jde - delta_t(year as f64) / 86400.0;
+    DateTime::from_timestamp_millis(((jd - UNIX_EPOCH_JD) * 86_400_000.0) as i64)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolarTerm {
+    pub index: usize,
+    pub date: NaiveDate,
+}
+
+impl SolarTerm {
+    pub fn name(&self) -> &'static str {
+        NAMES[self.index]
+    }
+    /// term `index` of gregorian `year`, dated in China
+    pub fn new(year: i32, index: usize) -> Option<Self> {
+        let china = FixedOffset::east_opt(CHINA_OFFSET).unwrap();
+        Some(Self { index, date: term_time(year, index)?.with_timezone(&china).date_naive() })
+    }
+}
+
+/// the term falling on `date`, or the first one after it.
+/// each term is a few hundred cosines, only the ones up to `date` are computed
+pub fn next_term(date: NaiveDate) -> Option<SolarTerm> {
+    (0..NAMES.len())
+        .map(|index| (date.year(), index))
+        .chain([(date.year() + 1, 0)])
+        .filter_map(|(year, index)| SolarTerm::new(year, index))
+        .find(|term| term.date >= date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn published_dates() {
+        // dates in China from the Purple Mountain Observatory tables
+        let cases = [
+            (2000, 5, date(2000, 3, 20)),
+            (2023, 2, date(2023, 2, 4)),
+            (2023, 11, date(2023, 6, 21)),
+            (2023, 23, date(2023, 12, 22)),
+            (2024, 0, date(2024, 1, 6)),
+            (2024, 2, date(2024, 2, 4)),
+            (2024, 5, date(2024, 3, 20)),
+            (2024, 6, date(2024, 4, 4)),
+            (2024, 17, date(2024, 9, 22)),
+            (2024, 23, date(2024, 12, 21)),
+            (2025, 0, date(2025, 1, 5)),
+            (2025, 18, date(2025, 10, 8)),
+        ];
+        for (year, index, expected) in cases {
+            assert_eq!(SolarTerm::new(year, index).map(|term| term.date), Some(expected), "{} {}", year, NAMES[index]);
+        }
+    }
+
+    #[test]
+    fn term_instant() {
+        // 2024 春分 at 2024-03-20 11:06 China time
+        let time = term_time(2024, 5).unwrap();
+        let expected = DateTime::parse_from_rfc3339("2024-03-20T03:06:00Z").unwrap();
+        assert!((time - expected.with_timezone(&Utc)).num_seconds().abs() < 120, "{}", time);
+    }
+
+    #[test]
+    fn next_term_across_the_year() {
+        assert_eq!(next_term(date(2024, 4, 4)), SolarTerm::new(2024, 6));
+        assert_eq!(next_term(date(2024, 4, 5)).map(|term| term.name()), Some("谷雨"));
+        assert_eq!(next_term(date(2024, 12, 25)), Some(SolarTerm { index: 0, date: date(2025, 1, 5) }));
+    }
+}
@@ -0,0 +1,9 @@
+//! time keeping logic of the clock that does not touch the hardware, built for the host
+//! as well so it can be tested there with `cargo test` in this directory
+
+pub mod alarm;
+pub mod calendar;
+pub mod net;
+pub mod sound;
+pub mod timer;
+pub mod tz;
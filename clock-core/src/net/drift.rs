@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+use std::time::Duration as StdDuration;
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/// syncs kept for the estimate
+const HISTORY: usize = 8;
+/// the estimate needs this many syncs over at least this many seconds
+const MIN_SAMPLES: usize = 3;
+const MIN_SPAN: f64 = 900.0;
+/// a crystal off by more than this is broken, not drifting
+const MAX_PPM: f64 = 500.0;
+/// offsets below this are slewed, anything larger is stepped
+const SLEW_LIMIT_MS: i64 = 128;
+
+/// what survives a reboot
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+pub struct DriftState {
+    pub ppm: f64,
+}
+
+/// estimates how fast the crystal runs from the corrections every sync needed.
+/// positive ppm means the local clock runs slow and has to be pushed forward
+pub struct DriftEstimator {
+    ppm: f64,
+    /// (utc seconds of the sync, all corrections applied up to then)
+    history: VecDeque<(f64, f64)>,
+    total: f64,
+}
+
+impl DriftEstimator {
+    pub fn new(state: DriftState) -> Self {
+        Self { ppm: state.ppm, history: VecDeque::with_capacity(HISTORY), total: 0.0 }
+    }
+    pub fn ppm(&self) -> f64 {
+        self.ppm
+    }
+    pub fn state(&self) -> DriftState {
+        DriftState { ppm: self.ppm }
+    }
+    pub fn should_slew(offset: &Duration) -> bool {
+        offset.num_milliseconds().abs() < SLEW_LIMIT_MS
+    }
+    /// correction the clock needs after running `elapsed` since the last one
+    pub fn correction(&self, elapsed: StdDuration) -> Duration {
+        Duration::nanoseconds((elapsed.as_secs_f64() * self.ppm * 1e3) as i64)
+    }
+    /// the clock was stepped by something we can not learn from, start over
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.total = 0.0;
+    }
+    /// `offset` measured at `at` utc seconds, with `correction` already applied since the last sync.
+    /// returns true when the estimate changed
+    pub fn add(&mut self, at: f64, offset: &Duration) -> bool {
+        if let Some((last, _)) = self.history.back() {
+            self.total += (at - last) * self.ppm / 1e6;
+        }
+        self.total += offset.num_microseconds().unwrap_or(0) as f64 / 1e6;
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back((at, self.total));
+        match self.slope() {
+            Some(slope) => {
+                self.ppm = (slope * 1e6).clamp(-MAX_PPM, MAX_PPM);
+                true
+            }
+            None => false,
+        }
+    }
+    /// least squares slope of the accumulated corrections over time
+    fn slope(&self) -> Option<f64> {
+        let (first, _) = *self.history.front()?;
+        let (last, _) = *self.history.back()?;
+        if self.history.len() < MIN_SAMPLES || last - first < MIN_SPAN {
+            return None;
+        }
+        let n = self.history.len() as f64;
+        let mean_x = self.history.iter().map(|(x, _)| x - first).sum::<f64>() / n;
+        let mean_y = self.history.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let (mut sxy, mut sxx) = (0.0, 0.0);
+        for (x, y) in self.history.iter() {
+            let dx = x - first - mean_x;
+            sxy += dx * (y - mean_y);
+            sxx += dx * dx;
+        }
+        match sxx > 0.0 {
+            true => Some(sxy / sxx),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// sync interval of the simulations, ten minutes
+    const PERIOD: f64 = 600.0;
+
+    fn seconds(value: f64) -> Duration {
+        Duration::microseconds((value * 1e6).round() as i64)
+    }
+
+    /// a crystal off by `ppm` synced every `PERIOD`, between syncs the estimator's own correction
+    /// is applied, the offsets each sync measured
+    fn simulate(drift: &mut DriftEstimator, ppm: f64, syncs: usize) -> Vec<f64> {
+        (1..=syncs)
+            .map(|sync| {
+                let corrected = drift.correction(StdDuration::from_secs_f64(PERIOD)).num_nanoseconds().unwrap() as f64 / 1e9;
+                let offset = PERIOD * ppm / 1e6 - corrected;
+                drift.add(sync as f64 * PERIOD, &seconds(offset));
+                offset
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recovers_constant_drift() {
+        // 12 ms every 10 minutes is 20 ppm slow, the clock is pushed forward
+        let mut drift = DriftEstimator::new(DriftState::default());
+        assert!(!drift.add(0.0, &seconds(0.012)));
+        assert!(!drift.add(PERIOD, &seconds(0.012)));
+        assert!(drift.add(2.0 * PERIOD, &seconds(0.012)));
+        assert!((drift.ppm() - 20.0).abs() < 1e-6, "{}", drift.ppm());
+        assert_eq!(drift.correction(StdDuration::from_secs(1000)).num_milliseconds(), 20);
+
+        // and a fast one is held back
+        let mut drift = DriftEstimator::new(DriftState::default());
+        for sync in 0..3 {
+            drift.add(sync as f64 * PERIOD, &seconds(-0.006));
+        }
+        assert!((drift.ppm() + 10.0).abs() < 1e-6, "{}", drift.ppm());
+        assert!(drift.correction(StdDuration::from_secs(1000)) < Duration::zero());
+    }
+
+    #[test]
+    fn needs_samples_and_span() {
+        let mut drift = DriftEstimator::new(DriftState { ppm: 7.0 });
+        // three syncs only 400 s apart in total
+        for sync in 0..3 {
+            assert!(!drift.add(sync as f64 * 200.0, &seconds(0.01)));
+        }
+        assert_eq!(drift.ppm(), 7.0);
+        assert!(drift.add(1000.0, &seconds(0.01)));
+    }
+
+    #[test]
+    fn clamps_a_broken_crystal() {
+        let mut drift = DriftEstimator::new(DriftState::default());
+        for sync in 0..3 {
+            drift.add(sync as f64 * PERIOD, &seconds(1.0));
+        }
+        assert_eq!(drift.ppm(), MAX_PPM);
+    }
+
+    #[test]
+    fn correction_converges() {
+        // the correction applied between syncs is counted once, the offsets left shrink to nothing
+        for start in [0.0, -50.0, 35.0] {
+            let mut drift = DriftEstimator::new(DriftState { ppm: start });
+            let offsets = simulate(&mut drift, 35.0, 12);
+            assert!((drift.ppm() - 35.0).abs() < 0.01, "from {}: {}", start, drift.ppm());
+            assert!(offsets.last().unwrap().abs() < 1e-5, "from {}: {:?}", start, offsets);
+        }
+    }
+
+    #[test]
+    fn reset_after_a_step() {
+        let mut drift = DriftEstimator::new(DriftState::default());
+        simulate(&mut drift, 20.0, 6);
+        // a step is not drift, the rate learned so far is kept and the history starts over
+        drift.reset();
+        assert!((drift.ppm() - 20.0).abs() < 0.01);
+        let base = 10.0 * PERIOD;
+        assert!(!drift.add(base, &Duration::zero()));
+        assert!(!drift.add(base + PERIOD, &Duration::zero()));
+        assert!(drift.add(base + 2.0 * PERIOD, &Duration::zero()));
+        assert!((drift.ppm() - 20.0).abs() < 0.01, "{}", drift.ppm());
+    }
+
+    #[test]
+    fn slew_or_step() {
+        assert!(DriftEstimator::should_slew(&Duration::milliseconds(127)));
+        assert!(DriftEstimator::should_slew(&Duration::milliseconds(-127)));
+        assert!(!DriftEstimator::should_slew(&Duration::milliseconds(128)));
+        assert!(!DriftEstimator::should_slew(&Duration::milliseconds(-128)));
+    }
+}
@@ -0,0 +1,132 @@
+use std::io::{BufRead, BufReader, Read};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+
+use crate::net::source::{TimeSample, TimeSource, PRIORITY_GPS};
+
+/// sentences read per sample before giving up on a fix
+const MAX_SENTENCES: usize = 32;
+/// nmea is sent after the second it describes, without pps that is all we know
+const NMEA_ACCURACY_MS: i64 = 500;
+
+/// checks the `*hh` xor checksum and returns the fields between `$` and `*`
+fn nmea_fields(line: &str) -> Option<Vec<&str>> {
+    let body = line.trim().strip_prefix('$')?;
+    let (body, checksum) = body.split_once('*')?;
+    let checksum = u8::from_str_radix(checksum, 16).ok()?;
+    match body.bytes().fold(0u8, |sum, b| sum ^ b) == checksum {
+        true => Some(body.split(',').collect()),
+        false => None,
+    }
+}
+
+/// utc time of a valid RMC sentence (`$GPRMC`, `$GNRMC`, ...)
+pub fn parse_rmc(line: &str) -> Option<DateTime<Utc>> {
+    let fields = nmea_fields(line)?;
+    if fields.len() < 10 || !fields[0].ends_with("RMC") || fields[2] != "A" {
+        return None;
+    }
+    let (time, date) = (fields[1], fields[9]);
+    if time.len() < 6 || date.len() != 6 {
+        return None;
+    }
+    let number = |s: &str| s.parse::<u32>().ok();
+    let millis = match time.get(6..) {
+        Some(fraction) if fraction.len() > 1 => (fraction.parse::<f32>().ok()? * 1000.0) as u32,
+        _ => 0,
+    };
+    let time = NaiveTime::from_hms_milli_opt(number(&time[0..2])?, number(&time[2..4])?, number(&time[4..6])?, millis)?;
+    // two digit year, 1980 to 2079
+    let year = match number(&date[4..6])? {
+        y if y < 80 => 2000 + y,
+        y => 1900 + y,
+    };
+    let date = NaiveDate::from_ymd_opt(year as i32, number(&date[2..4])?, number(&date[0..2])?)?;
+    Some(date.and_time(time).and_utc())
+}
+
+/// a gps receiver talking nmea 0183 over any byte stream (an uart), a read of 0 bytes
+/// means nothing arrived in time
+pub struct GpsSource<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> GpsSource<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+}
+
+impl<R: Read> TimeSource for GpsSource<R> {
+    fn name(&self) -> &str {
+        "gps"
+    }
+    fn priority(&self) -> u8 {
+        PRIORITY_GPS
+    }
+    fn sample(&mut self) -> anyhow::Result<TimeSample> {
+        // a fresh buffer each time, lines left over from the last sample are long out of date
+        let mut reader = BufReader::new(&mut self.reader);
+        let mut line = String::new();
+        for _ in 0..MAX_SENTENCES {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let local = SystemTime::now();
+            if let Some(utc) = parse_rmc(&line) {
+                let mut sample = TimeSample::at(self.name(), utc, local, Duration::milliseconds(NMEA_ACCURACY_MS));
+                sample.stratum = 0;
+                sample.reference_id = *b"GPS\0";
+                return Ok(sample);
+            }
+        }
+        Err(anyhow::Error::msg("no gps fix"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RMC: &str = "$GPRMC,123519.50,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*5E";
+
+    /// the sentence with its checksum recomputed after an edit
+    fn sentence(body: &str) -> String {
+        format!("${}*{:02X}", body, body.bytes().fold(0u8, |sum, b| sum ^ b))
+    }
+
+    #[test]
+    fn rmc_time() {
+        let rmc = sentence(&RMC[1..RMC.len() - 3]);
+        let utc = parse_rmc(&rmc).unwrap();
+        assert_eq!(utc.to_rfc3339(), "1994-03-23T12:35:19.500+00:00");
+        assert_eq!(parse_rmc(&sentence("GNRMC,000000,A,,,,,,,010130,,")).unwrap().to_rfc3339(), "2030-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn rmc_rejected() {
+        let rmc = sentence(&RMC[1..RMC.len() - 3]);
+        // bad checksum, no fix, not an RMC
+        assert!(parse_rmc(&rmc.replace("123519", "123518")).is_none());
+        assert!(parse_rmc(&sentence(&RMC[1..RMC.len() - 3].replace(",A,", ",V,"))).is_none());
+        assert!(parse_rmc(&sentence("GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,")).is_none());
+    }
+
+    #[test]
+    fn source_skips_other_sentences() {
+        let stream = format!(
+            "{}\r\n{}\r\n{}\r\n",
+            sentence("GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,"),
+            sentence("GPRMC,123520,V,,,,,,,230394,,"),
+            sentence("GPRMC,123521,A,,,,,,,230394,,"),
+        );
+        let mut source = GpsSource::new(stream.as_bytes());
+        let sample = source.sample().unwrap();
+        assert_eq!((sample.stratum, &sample.reference_id), (0, b"GPS\0"));
+        let expected = NaiveDate::from_ymd_opt(1994, 3, 23).unwrap().and_hms_opt(12, 35, 21).unwrap().and_utc();
+        assert!((sample.offset - (expected - Utc::now())).num_seconds().abs() <= 1);
+        assert!(source.sample().is_err());
+    }
+}
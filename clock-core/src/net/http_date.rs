@@ -0,0 +1,159 @@
+use std::time::{Instant, SystemTime};
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use log::error;
+
+use crate::net::source::{TimeSample, TimeSource, PRIORITY_HTTP};
+
+/// `Date` only has whole seconds, the real time is anywhere in that second
+const DATE_RESOLUTION_MS: i64 = 1000;
+
+/// a `Date` header in any of the three formats RFC 7231 section 7.1.1.1 asks to accept
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    // rfc 850 and asctime
+    ["%A, %d-%b-%y %H:%M:%S GMT", "%a %b %e %H:%M:%S %Y"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+        .map(|date| date.and_utc())
+}
+
+/// sends a HEAD request and hands back the `Date` header
+pub trait HeadRequest {
+    fn date(&mut self, url: &str) -> anyhow::Result<Option<String>>;
+}
+
+/// time from the `Date` header of web servers, for networks that drop ntp
+pub struct HttpSource<H: HeadRequest> {
+    urls: Vec<String>,
+    head: H,
+}
+
+impl<H: HeadRequest> HttpSource<H> {
+    pub fn new<S: AsRef<str>>(urls: &[S], head: H) -> Self {
+        Self { urls: urls.iter().map(|s| s.as_ref().into()).collect(), head }
+    }
+    fn request(&mut self, url: &str) -> anyhow::Result<TimeSample> {
+        let (local, start) = (SystemTime::now(), Instant::now());
+        let date = self.head.date(url)?.ok_or(anyhow::Error::msg("no date header"))?;
+        let round_trip = Duration::from_std(start.elapsed())?;
+        let date = parse_http_date(&date).ok_or(anyhow::Error::msg(format!("bad date header: {}", date)))?;
+        // the header was written around the middle of the round trip and the middle of its second
+        let resolution = Duration::milliseconds(DATE_RESOLUTION_MS);
+        let local = DateTime::<Utc>::from(local) + round_trip / 2;
+        let mut sample = TimeSample::at(url, date + resolution / 2, local.into(), round_trip / 2 + resolution / 2);
+        sample.delay = round_trip;
+        Ok(sample)
+    }
+}
+
+impl<H: HeadRequest> TimeSource for HttpSource<H> {
+    fn name(&self) -> &str {
+        "http"
+    }
+    fn priority(&self) -> u8 {
+        PRIORITY_HTTP
+    }
+    fn available(&self) -> bool {
+        !self.urls.is_empty()
+    }
+    /// every url is asked, the shortest round trip wins
+    fn sample(&mut self) -> anyhow::Result<TimeSample> {
+        let mut best: Option<TimeSample> = None;
+        for url in self.urls.clone() {
+            match self.request(&url) {
+                Ok(sample) if best.as_ref().is_none_or(|b| sample.accuracy < b.accuracy) => best = Some(sample),
+                Ok(_) => {}
+                Err(e) => error!("http date {} failed! {}", url, e),
+            }
+        }
+        let mut sample = best.ok_or(anyhow::Error::msg("no http server answered"))?;
+        sample.source = self.name().into();
+        Ok(sample)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration as StdDuration;
+
+    use chrono::NaiveDate;
+
+    #[test]
+    fn rfc_7231_examples() {
+        let expected = NaiveDate::from_ymd_opt(1994, 11, 6).unwrap().and_hms_opt(8, 49, 37).unwrap().and_utc();
+        for value in ["Sun, 06 Nov 1994 08:49:37 GMT", "Sunday, 06-Nov-94 08:49:37 GMT", "Sun Nov  6 08:49:37 1994",
+                      " Sun, 06 Nov 1994 08:49:37 GMT\r\n"] {
+            assert_eq!(parse_http_date(value), Some(expected), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn other_dates() {
+        let date = |value| parse_http_date(value).map(|date| date.to_rfc3339());
+        assert_eq!(date("Tue, 15 Nov 2033 23:59:60 GMT"), Some(String::from("2033-11-15T23:59:60+00:00")));
+        assert_eq!(date("Wednesday, 01-Jan-25 00:00:00 GMT"), Some(String::from("2025-01-01T00:00:00+00:00")));
+        assert_eq!(date("Thu Jan 16 10:00:00 2025"), Some(String::from("2025-01-16T10:00:00+00:00")));
+        assert_eq!(date("2025-01-16T10:00:00Z"), None);
+        assert_eq!(date(""), None);
+    }
+
+    /// answers each url after `delay` with `date`, `None` for a failed request
+    struct FakeHead {
+        answers: Vec<(&'static str, StdDuration, Option<Option<&'static str>>)>,
+    }
+
+    impl HeadRequest for FakeHead {
+        fn date(&mut self, url: &str) -> anyhow::Result<Option<String>> {
+            let (_, delay, date) = self.answers.iter().find(|(u, _, _)| *u == url).unwrap();
+            thread::sleep(*delay);
+            date.map(|date| date.map(String::from)).ok_or(anyhow::Error::msg("unreachable"))
+        }
+    }
+
+    const DATE: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+
+    #[test]
+    fn offset_and_accuracy() {
+        let mut source = HttpSource::new(&["a"], FakeHead { answers: vec![("a", StdDuration::from_millis(200), Some(Some(DATE)))] });
+        let before = Utc::now();
+        let sample = source.sample().unwrap();
+        let after = Utc::now();
+        // the middle of the date's second against the middle of the round trip
+        let date = parse_http_date(DATE).unwrap() + Duration::milliseconds(500);
+        let slack = Duration::milliseconds(1);
+        assert!(sample.delay >= Duration::milliseconds(200) && sample.delay <= after - before + slack);
+        assert_eq!(sample.accuracy, sample.delay / 2 + Duration::milliseconds(500));
+        assert!(sample.offset >= date - (after - sample.delay / 2) - slack);
+        assert!(sample.offset <= date - (before + sample.delay / 2) + slack);
+        assert_eq!(sample.source, "http");
+    }
+
+    #[test]
+    fn shortest_round_trip_wins() {
+        let head = FakeHead {
+            answers: vec![
+                ("down", StdDuration::ZERO, None),
+                ("slow", StdDuration::from_millis(150), Some(Some(DATE))),
+                ("no-date", StdDuration::ZERO, Some(None)),
+                ("fast", StdDuration::from_millis(10), Some(Some("Sun, 06 Nov 1994 08:49:38 GMT"))),
+                ("garbled", StdDuration::ZERO, Some(Some("yesterday"))),
+            ],
+        };
+        let mut source = HttpSource::new(&["down", "slow", "no-date", "fast", "garbled"], head);
+        let sample = source.sample().unwrap();
+        assert!(sample.delay < Duration::milliseconds(150), "{}", sample.delay);
+
+        let mut source = HttpSource::new(&["down", "garbled"], FakeHead { answers: vec![
+            ("down", StdDuration::ZERO, None),
+            ("garbled", StdDuration::ZERO, Some(Some("yesterday"))),
+        ] });
+        assert!(source.sample().is_err());
+        assert!(!HttpSource::new::<&str>(&[], FakeHead { answers: vec![] }).available());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod drift;
+pub mod gps;
+pub mod http_date;
+pub mod ntp;
+pub mod peer;
+pub mod rtc;
+pub mod select;
+pub mod server;
+pub mod source;
@@ -0,0 +1,600 @@
+use std::fmt::{Display, Formatter};
+use std::io::{Cursor, ErrorKind, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration as StdDuration, SystemTime, UNIX_EPOCH};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use chrono::prelude::*;
+use chrono::Duration;
+
+pub const NTP_PORT: u16 = 123;
+pub const PACKET_SIZE: usize = 48;
+/// seconds from 1900-01-01 (ntp epoch) to 1970-01-01 (unix epoch)
+pub const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+
+/// unix seconds the firmware was built at, `SOURCE_DATE_EPOCH` for a reproducible build,
+/// the clock can never be earlier than this
+const BUILD_PIVOT: i64 = parse_timestamp(option_env!("BUILD_TIMESTAMP"), 1_704_067_200);
+
+const fn parse_timestamp(value: Option<&str>, default: i64) -> i64 {
+    let bytes = match value {
+        None => return default,
+        Some(value) => value.as_bytes(),
+    };
+    let (mut i, mut seconds) = (0, 0i64);
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            return default;
+        }
+        seconds = seconds * 10 + (bytes[i] - b'0') as i64;
+        i += 1;
+    }
+    seconds
+}
+
+pub(crate) const MODE_CLIENT: u8 = 3;
+pub(crate) const MODE_SERVER: u8 = 4;
+/// LI = 0, VN = 4, Mode = 3 (client)
+const CLIENT_REQUEST: u8 = (4 << 3) | MODE_CLIENT;
+const MAX_STRATUM: u8 = 15;
+
+#[derive(Debug)]
+pub enum NtpError {
+    Io(std::io::Error),
+    /// no answer within the receive timeout
+    Timeout,
+    /// answer shorter than a ntp header
+    ShortPacket(usize),
+    /// answer does not echo our transmit timestamp
+    Mismatch,
+    /// stratum 0 answer, the reference id carries the kiss code
+    KissOfDeath([u8; 4]),
+    /// answer is not from a server
+    BadMode(u8),
+    /// server clock is not synchronized itself
+    Unsynchronized,
+    /// none of the servers answered
+    Unreachable,
+    /// answers do not agree on the time
+    NoMajority,
+}
+
+impl Display for NtpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NtpError::Io(e) => write!(f, "io error: {}", e),
+            NtpError::Timeout => f.write_str("receive timed out"),
+            NtpError::ShortPacket(size) => write!(f, "response too short: {} bytes", size),
+            NtpError::Mismatch => f.write_str("response does not match request"),
+            NtpError::KissOfDeath(code) => write!(f, "kiss-o'-death: {}", String::from_utf8_lossy(code)),
+            NtpError::BadMode(mode) => write!(f, "unexpected mode: {}", mode),
+            NtpError::Unsynchronized => f.write_str("server is unsynchronized"),
+            NtpError::Unreachable => f.write_str("no server answered"),
+            NtpError::NoMajority => f.write_str("no majority agreement between servers"),
+        }
+    }
+}
+
+impl std::error::Error for NtpError {}
+
+impl NtpError {
+    /// worth asking the same server again right away
+    pub const fn is_transient(&self) -> bool {
+        matches!(self, NtpError::Io(_) | NtpError::Timeout | NtpError::ShortPacket(_) | NtpError::Mismatch)
+    }
+}
+
+/// leap indicator, announces a leap second at the end of the current month (UTC)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Leap {
+    #[default]
+    None,
+    /// last minute has 61 seconds, 23:59:60 follows 23:59:59
+    Insert,
+    /// last minute has 59 seconds, 23:59:59 is skipped
+    Delete,
+    Unsynchronized,
+}
+
+impl From<u8> for Leap {
+    fn from(value: u8) -> Self {
+        match value & 0x03 {
+            0 => Leap::None,
+            1 => Leap::Insert,
+            2 => Leap::Delete,
+            _ => Leap::Unsynchronized,
+        }
+    }
+}
+
+impl From<Leap> for u8 {
+    fn from(value: Leap) -> Self {
+        match value {
+            Leap::None => 0,
+            Leap::Insert => 1,
+            Leap::Delete => 2,
+            Leap::Unsynchronized => 3,
+        }
+    }
+}
+
+impl From<std::io::Error> for NtpError {
+    fn from(value: std::io::Error) -> Self {
+        match value.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => NtpError::Timeout,
+            _ => NtpError::Io(value),
+        }
+    }
+}
+
+/// 32.32 fixed point ntp timestamp
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NtpTimestamp {
+    pub seconds: u32,
+    pub fraction: u32,
+}
+
+impl NtpTimestamp {
+    pub fn now() -> Self {
+        Self::from_unix(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default())
+    }
+    /// the era number is not carried on the wire, seconds simply wrap every 2^32
+    pub fn from_unix(value: StdDuration) -> Self {
+        let seconds = (value.as_secs() + NTP_UNIX_OFFSET) as u32;
+        let fraction = ((u64::from(value.subsec_nanos()) << 32) / 1_000_000_000) as u32;
+        Self { seconds, fraction }
+    }
+    /// time since the unix epoch, picking the era (136 years each, era 1 starts
+    /// 2036-02-07 06:28:16 UTC) that lands within 68 years of `pivot` unix seconds
+    pub fn to_unix(&self, pivot: i64) -> Duration {
+        let pivot = pivot + NTP_UNIX_OFFSET as i64;
+        let delta = i64::from(self.seconds.wrapping_sub(pivot as u32) as i32);
+        let nanos = (u64::from(self.fraction) * 1_000_000_000) >> 32;
+        Duration::seconds(pivot + delta - NTP_UNIX_OFFSET as i64) + Duration::nanoseconds(nanos as i64)
+    }
+    pub const fn as_u64(&self) -> u64 {
+        (self.seconds as u64) << 32 | self.fraction as u64
+    }
+    pub const fn from_u64(value: u64) -> Self {
+        Self { seconds: (value >> 32) as u32, fraction: value as u32 }
+    }
+    pub const fn is_zero(&self) -> bool {
+        self.seconds == 0 && self.fraction == 0
+    }
+}
+
+/// ntp short format (16.16) to duration, used by root delay/dispersion
+fn short_to_duration(value: u32) -> Duration {
+    Duration::nanoseconds(((u64::from(value) * 1_000_000_000) >> 16) as i64)
+}
+
+/// duration to ntp short format (16.16), saturating on both ends
+pub fn duration_to_short(value: Duration) -> u32 {
+    let nanos = value.num_nanoseconds().unwrap_or(i64::MAX).max(0) as u64;
+    ((u128::from(nanos) << 16) / 1_000_000_000).min(u128::from(u32::MAX)) as u32
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Packet {
+    pub li_vn_mode: u8,
+    pub stratum: u8,
+    pub poll: i8,
+    pub precision: i8,
+    pub root_delay: u32,
+    pub root_dispersion: u32,
+    pub reference_id: [u8; 4],
+    pub reference: NtpTimestamp,
+    pub originate: NtpTimestamp,
+    pub receive: NtpTimestamp,
+    pub transmit: NtpTimestamp,
+}
+
+impl Packet {
+    /// client request, `transmit` is echoed back by the server as originate timestamp
+    pub fn request(transmit: NtpTimestamp) -> Self {
+        Self {
+            li_vn_mode: CLIENT_REQUEST,
+            transmit,
+            ..Default::default()
+        }
+    }
+    pub fn leap(&self) -> Leap {
+        Leap::from(self.li_vn_mode >> 6)
+    }
+    pub const fn version(&self) -> u8 {
+        (self.li_vn_mode >> 3) & 0x07
+    }
+    pub const fn mode(&self) -> u8 {
+        self.li_vn_mode & 0x07
+    }
+    pub fn root_delay(&self) -> Duration {
+        short_to_duration(self.root_delay)
+    }
+    pub fn root_dispersion(&self) -> Duration {
+        short_to_duration(self.root_dispersion)
+    }
+
+    /// reject answers that must not be used to set the clock
+    pub fn validate(&self) -> Result<(), NtpError> {
+        if self.mode() != MODE_SERVER {
+            return Err(NtpError::BadMode(self.mode()));
+        }
+        if self.stratum == 0 {
+            return Err(NtpError::KissOfDeath(self.reference_id));
+        }
+        if self.leap() == Leap::Unsynchronized || self.stratum > MAX_STRATUM || self.transmit.is_zero() {
+            return Err(NtpError::Unsynchronized);
+        }
+        Ok(())
+    }
+
+    pub fn pack(&self) -> [u8; PACKET_SIZE] {
+        let mut buffer = [0u8; PACKET_SIZE];
+        let mut writer = Cursor::new(&mut buffer[..]);
+        // writes into a fixed 48 bytes buffer can not fail
+        writer.write_u8(self.li_vn_mode).unwrap();
+        writer.write_u8(self.stratum).unwrap();
+        writer.write_i8(self.poll).unwrap();
+        writer.write_i8(self.precision).unwrap();
+        writer.write_u32::<BigEndian>(self.root_delay).unwrap();
+        writer.write_u32::<BigEndian>(self.root_dispersion).unwrap();
+        writer.write_all(&self.reference_id).unwrap();
+        for timestamp in [self.reference, self.originate, self.receive, self.transmit] {
+            writer.write_u64::<BigEndian>(timestamp.as_u64()).unwrap();
+        }
+        buffer
+    }
+
+    pub fn unpack(buffer: &[u8; PACKET_SIZE]) -> Result<Self, NtpError> {
+        let mut reader = Cursor::new(buffer);
+        let li_vn_mode = reader.read_u8()?;
+        let stratum = reader.read_u8()?;
+        let poll = reader.read_i8()?;
+        let precision = reader.read_i8()?;
+        let root_delay = reader.read_u32::<BigEndian>()?;
+        let root_dispersion = reader.read_u32::<BigEndian>()?;
+        let mut reference_id = [0u8; 4];
+        std::io::Read::read_exact(&mut reader, &mut reference_id)?;
+        let mut timestamp = || -> Result<NtpTimestamp, NtpError> {
+            Ok(NtpTimestamp::from_u64(reader.read_u64::<BigEndian>()?))
+        };
+        Ok(Self {
+            li_vn_mode,
+            stratum,
+            poll,
+            precision,
+            root_delay,
+            root_dispersion,
+            reference_id,
+            reference: timestamp()?,
+            originate: timestamp()?,
+            receive: timestamp()?,
+            transmit: timestamp()?,
+        })
+    }
+}
+
+pub struct NtpClient {
+    timeout: StdDuration,
+    retries: u32,
+}
+
+impl Default for NtpClient {
+    fn default() -> Self {
+        Self::new(StdDuration::from_secs(2), 2)
+    }
+}
+
+impl NtpClient {
+    /// `retries` extra attempts are made after the first one fails
+    pub fn new(timeout: StdDuration, retries: u32) -> NtpClient {
+        NtpClient { timeout, retries }
+    }
+    /// `server` is a host name or address, optionally followed by `:port`, an IPv6 address
+    /// with a port goes in brackets, `[2001:db8::1]:123`
+    pub fn request(&self, server: &str) -> Result<Response, NtpError> {
+        let mut attempt = 0;
+        loop {
+            match self.exchange(server) {
+                Ok(response) => return Ok(response),
+                Err(e) if attempt >= self.retries || !e.is_transient() => return Err(e),
+                Err(_) => attempt += 1,
+            }
+        }
+    }
+    fn exchange(&self, server: &str) -> Result<Response, NtpError> {
+        let address = resolve(server)?;
+        let unspecified = match address {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let client = UdpSocket::bind((unspecified, 0))?;
+        client.set_read_timeout(Some(self.timeout))?;
+        client.connect(address)?;
+        let originate = NtpTimestamp::now();
+        client.send(&Packet::request(originate).pack())?;
+        let mut buf = [0; PACKET_SIZE];
+        let size = client.recv(&mut buf)?;
+        let local = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let destination = NtpTimestamp::from_unix(local);
+        if size < PACKET_SIZE {
+            return Err(NtpError::ShortPacket(size));
+        }
+        let packet = Packet::unpack(&buf)?;
+        if packet.originate != originate {
+            return Err(NtpError::Mismatch);
+        }
+        packet.validate()?;
+        let mut response = Response::new(packet, destination, local.as_secs() as i64);
+        response.peer = client.peer_addr().ok();
+        Ok(response)
+    }
+}
+
+/// the address of `server`, port 123 unless it names one
+fn resolve(server: &str) -> std::io::Result<SocketAddr> {
+    if let Ok(address) = server.parse::<SocketAddr>() {
+        return Ok(address);
+    }
+    // a bare address, IPv6 ones with or without brackets
+    let bare = server.strip_prefix('[').and_then(|s| s.strip_suffix(']')).unwrap_or(server);
+    if let Ok(ip) = bare.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, NTP_PORT));
+    }
+    let (host, port) = match server.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| std::io::Error::new(ErrorKind::InvalidInput, "invalid port"))?),
+        None => (server, NTP_PORT),
+    };
+    let mut addresses = (host, port).to_socket_addrs()?;
+    addresses.next().ok_or(std::io::Error::new(ErrorKind::NotFound, "no address"))
+}
+
+/// poll interval, doubled from `min` on every consecutive failure up to `max`
+pub struct Backoff {
+    interval: u32,
+    min: u32,
+    max: u32,
+    failures: u32,
+}
+
+impl Backoff {
+    pub fn new(interval: u32, min: u32, max: u32) -> Self {
+        Self { interval, min, max: max.max(min), failures: 0 }
+    }
+    /// seconds to wait after a successful sync
+    pub fn success(&mut self) -> u32 {
+        self.failures = 0;
+        self.interval
+    }
+    /// seconds to wait after a failed sync
+    pub fn failure(&mut self) -> u32 {
+        let next = self.min.saturating_mul(1u32 << self.failures.min(16)).min(self.max);
+        self.failures += 1;
+        next
+    }
+    pub const fn failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+pub struct Response {
+    pub packet: Packet,
+    /// T4, local time the response arrived
+    pub destination: NtpTimestamp,
+    /// correction to add to the local clock
+    pub offset: Duration,
+    /// round trip delay, not counting the server processing time
+    pub delay: Duration,
+    pub peer: Option<SocketAddr>,
+}
+
+impl Response {
+    /// offset and delay as defined in RFC 4330 section 5:
+    /// offset = ((T2 - T1) + (T3 - T4)) / 2, delay = (T4 - T1) - (T3 - T2)
+    ///
+    /// `local` is the local clock in unix seconds at T4, our own timestamps are resolved
+    /// against it, the server's against it too unless the clock is older than the build
+    pub fn new(packet: Packet, destination: NtpTimestamp, local: i64) -> Self {
+        let server = local.max(BUILD_PIVOT);
+        let (t1, t4) = (packet.originate.to_unix(local), destination.to_unix(local));
+        let (t2, t3) = (packet.receive.to_unix(server), packet.transmit.to_unix(server));
+        let offset = ((t2 - t1) + (t3 - t4)) / 2;
+        let delay = (t4 - t1) - (t3 - t2);
+        Self { packet, destination, offset, delay, peer: None }
+    }
+
+    /// server's error budget: root dispersion, half the root delay and its clock precision
+    pub fn dispersion(&self) -> Duration {
+        let precision = 2f64.powi(i32::from(self.packet.precision)) * 1e9;
+        self.packet.root_dispersion() + self.packet.root_delay() / 2 + Duration::nanoseconds(precision as i64)
+    }
+
+    /// local time at `now` corrected by the measured offset
+    pub fn corrected(&self, now: SystemTime) -> DateTime<Utc> {
+        DateTime::<Utc>::from(now) + self.offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2036-02-07 06:28:16 UTC, ntp seconds wrap to 0
+    const ERA_1: i64 = 2_085_978_496;
+    /// 2024-01-01 00:00:00 UTC
+    const PIVOT_2024: i64 = 1_704_067_200;
+
+    fn unix(seconds: i64, nanos: u32) -> StdDuration {
+        StdDuration::new(seconds as u64, nanos)
+    }
+
+    #[test]
+    fn wire_seconds_wrap_at_era_boundary() {
+        assert_eq!(NtpTimestamp::from_unix(unix(ERA_1 - 1, 0)).seconds, u32::MAX);
+        assert_eq!(NtpTimestamp::from_unix(unix(ERA_1, 0)).seconds, 0);
+        assert_eq!(NtpTimestamp::from_unix(unix(ERA_1 + 1, 0)).seconds, 1);
+    }
+
+    #[test]
+    fn round_trip_across_rollover() {
+        for seconds in [ERA_1 - 2, ERA_1 - 1, ERA_1, ERA_1 + 1, ERA_1 + 2] {
+            let timestamp = NtpTimestamp::from_unix(unix(seconds, 500_000_000));
+            let back = timestamp.to_unix(PIVOT_2024);
+            assert_eq!(back.num_seconds(), seconds);
+            assert_eq!(back.num_milliseconds() % 1000, 500);
+        }
+    }
+
+    #[test]
+    fn pivot_after_rollover_reads_both_eras() {
+        let pivot = ERA_1 + 3600;
+        assert_eq!(NtpTimestamp { seconds: u32::MAX, fraction: 0 }.to_unix(pivot).num_seconds(), ERA_1 - 1);
+        assert_eq!(NtpTimestamp { seconds: 0, fraction: 0 }.to_unix(pivot).num_seconds(), ERA_1);
+    }
+
+    #[test]
+    fn reply_below_unix_offset_is_next_era() {
+        // used to underflow when subtracting the unix offset from era 0 seconds
+        let timestamp = NtpTimestamp { seconds: 1000, fraction: 0 };
+        assert_eq!(timestamp.to_unix(PIVOT_2024).num_seconds(), ERA_1 + 1000);
+    }
+
+    #[test]
+    fn reply_below_pivot_stays_in_pivot_era() {
+        let timestamp = NtpTimestamp::from_unix(unix(PIVOT_2024 - 10, 0));
+        assert_eq!(timestamp.to_unix(PIVOT_2024).num_seconds(), PIVOT_2024 - 10);
+    }
+
+    #[test]
+    fn pivot_picks_the_era() {
+        let timestamp = NtpTimestamp { seconds: 3_000_000_000, fraction: 0 };
+        let era_0 = 3_000_000_000 - NTP_UNIX_OFFSET as i64;
+        // 2001 reads it as 1995, 2112 as 2131
+        assert_eq!(timestamp.to_unix(1_000_000_000).num_seconds(), era_0);
+        assert_eq!(timestamp.to_unix(4_500_000_000).num_seconds(), era_0 + (1 << 32));
+    }
+
+    #[test]
+    fn fraction_keeps_sub_second() {
+        let timestamp = NtpTimestamp { seconds: (PIVOT_2024 + NTP_UNIX_OFFSET as i64) as u32, fraction: 1 << 31 };
+        assert_eq!(timestamp.to_unix(PIVOT_2024).num_milliseconds(), PIVOT_2024 * 1000 + 500);
+        assert_eq!(NtpTimestamp::from_unix(unix(PIVOT_2024, 250_000_000)).fraction, 1 << 30);
+    }
+
+    fn at(seconds: i64, millis: u32) -> NtpTimestamp {
+        NtpTimestamp::from_unix(unix(seconds, millis * 1_000_000))
+    }
+
+    #[test]
+    fn offset_and_delay() {
+        let mut packet = Packet { originate: at(PIVOT_2024, 0), ..Default::default() };
+        packet.receive = at(PIVOT_2024 + 10, 250);
+        packet.transmit = at(PIVOT_2024 + 10, 500);
+        let response = Response::new(packet, at(PIVOT_2024, 750), PIVOT_2024);
+        // ((10.25 - 0) + (10.5 - 0.75)) / 2 and (0.75 - 0) - (10.5 - 10.25)
+        assert_eq!(response.offset.num_milliseconds(), 10_000);
+        assert_eq!(response.delay.num_milliseconds(), 500);
+    }
+
+    /// a server on loopback answering every request once through `reply`
+    fn stand_in(reply: impl Fn(Packet) -> Packet + Send + 'static) -> String {
+        stand_in_on("127.0.0.1:0", reply)
+    }
+
+    fn stand_in_on(bind: &str, reply: impl Fn(Packet) -> Packet + Send + 'static) -> String {
+        let socket = UdpSocket::bind(bind).unwrap();
+        let address = socket.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; PACKET_SIZE];
+            while let Ok((_, peer)) = socket.recv_from(&mut buf) {
+                let request = Packet::unpack(&buf).unwrap();
+                socket.send_to(&reply(request).pack(), peer).unwrap();
+            }
+        });
+        address
+    }
+
+    fn server_reply(request: Packet, receive: NtpTimestamp, transmit: NtpTimestamp) -> Packet {
+        Packet {
+            li_vn_mode: (4 << 3) | MODE_SERVER,
+            stratum: 2,
+            reference_id: *b"GPS\0",
+            originate: request.transmit,
+            receive,
+            transmit,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn client_against_stand_in_server() {
+        // the server clock reads 2030-01-01 00:00:00.375 for the whole exchange
+        const SERVER: i64 = 1_893_456_000;
+        let server = stand_in(|request| server_reply(request, at(SERVER, 375), at(SERVER, 375)));
+        let client = NtpClient::new(StdDuration::from_secs(2), 0);
+        let before = DateTime::<Utc>::from(SystemTime::now());
+        let response = client.request(&server).unwrap();
+        let after = DateTime::<Utc>::from(SystemTime::now());
+        let time = DateTime::from_timestamp(SERVER, 375_000_000).unwrap();
+        let slack = Duration::milliseconds(1);
+        // T1 and T4 both fall between before and after
+        assert!(response.offset >= time - after - slack && response.offset <= time - before + slack);
+        assert!(response.delay >= -slack && response.delay <= after - before + slack);
+        assert_eq!(response.packet.receive.fraction, 0x6000_0000);
+        assert_eq!(response.packet.transmit.to_unix(PIVOT_2024).num_milliseconds() % 1000, 375);
+        assert_eq!(response.packet.stratum, 2);
+        assert_eq!(response.peer.map(|peer| peer.to_string()), Some(server));
+    }
+
+    #[test]
+    fn client_rejects_wrong_originate() {
+        let server = stand_in(|request| {
+            let mut reply = server_reply(request, NtpTimestamp::now(), NtpTimestamp::now());
+            reply.originate.fraction ^= 1;
+            reply
+        });
+        let client = NtpClient::new(StdDuration::from_secs(2), 0);
+        assert!(matches!(client.request(&server), Err(NtpError::Mismatch)));
+    }
+
+    #[test]
+    fn client_reports_kiss_of_death() {
+        let server = stand_in(|request| {
+            let mut reply = server_reply(request, NtpTimestamp::now(), NtpTimestamp::now());
+            reply.stratum = 0;
+            reply.reference_id = *b"RATE";
+            reply
+        });
+        let client = NtpClient::new(StdDuration::from_secs(2), 0);
+        assert!(matches!(client.request(&server), Err(NtpError::KissOfDeath(code)) if &code == b"RATE"));
+    }
+
+    #[test]
+    fn build_timestamp_parse() {
+        assert_eq!(parse_timestamp(Some("1704067200"), 0), PIVOT_2024);
+        assert_eq!(parse_timestamp(Some("17x"), 7), 7);
+        assert_eq!(parse_timestamp(None, 7), 7);
+    }
+
+    #[test]
+    fn server_addresses() {
+        let address = |server| resolve(server).unwrap().to_string();
+        assert_eq!(address("192.0.2.1"), "192.0.2.1:123");
+        assert_eq!(address("192.0.2.1:1123"), "192.0.2.1:1123");
+        assert_eq!(address("2001:db8::1"), "[2001:db8::1]:123");
+        assert_eq!(address("[2001:db8::1]"), "[2001:db8::1]:123");
+        assert_eq!(address("[2001:db8::1]:1123"), "[2001:db8::1]:1123");
+        assert!(["127.0.0.1:123", "[::1]:123"].contains(&address("localhost").as_str()));
+        assert!(["127.0.0.1:1123", "[::1]:1123"].contains(&address("localhost:1123").as_str()));
+        assert!(resolve("localhost:port").is_err());
+    }
+
+    #[test]
+    fn client_over_ipv6() {
+        const SERVER: i64 = 1_893_456_000;
+        let server = stand_in_on("[::1]:0", |request| server_reply(request, at(SERVER, 0), at(SERVER, 0)));
+        assert!(server.starts_with("[::1]:"));
+        let response = NtpClient::new(StdDuration::from_secs(2), 0).request(&server).unwrap();
+        assert_eq!(response.peer.map(|peer| peer.to_string()), Some(server));
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+use log::{error, warn};
+
+use crate::net::ntp::{NtpClient, NtpError};
+use crate::net::select::Sample;
+
+/// RATE kisses hold a server back for this long, doubled on every repeat
+const RATE_HOLD: Duration = Duration::from_secs(64);
+const RATE_HOLD_MAX: Duration = Duration::from_secs(36 * 3600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PeerState {
+    Active,
+    /// asked to slow down, skipped until the deadline
+    Hold(Instant),
+    /// refused access, never asked again
+    Denied,
+}
+
+pub struct Peer {
+    pub server: String,
+    state: PeerState,
+    rate_kisses: u32,
+}
+
+impl Peer {
+    pub fn new(server: &str) -> Self {
+        Self { server: server.into(), state: PeerState::Active, rate_kisses: 0 }
+    }
+    pub fn available(&self, now: Instant) -> bool {
+        match self.state {
+            PeerState::Active => true,
+            PeerState::Hold(until) => now >= until,
+            PeerState::Denied => false,
+        }
+    }
+    /// reacts to a kiss-o'-death code as RFC 4330 section 8 asks
+    pub fn kiss(&mut self, code: &[u8; 4], now: Instant) {
+        match code {
+            b"RATE" => {
+                let hold = RATE_HOLD.saturating_mul(1 << self.rate_kisses.min(16)).min(RATE_HOLD_MAX);
+                self.rate_kisses += 1;
+                self.state = PeerState::Hold(now + hold);
+            }
+            b"DENY" | b"RSTR" => self.state = PeerState::Denied,
+            _ => {}
+        }
+    }
+    pub fn answered(&mut self) {
+        self.state = PeerState::Active;
+        self.rate_kisses = 0;
+    }
+}
+
+pub struct Peers {
+    peers: Vec<Peer>,
+}
+
+impl Peers {
+    pub fn new<S: AsRef<str>>(servers: &[S]) -> Self {
+        Self { peers: servers.iter().map(|s| Peer::new(s.as_ref())).collect() }
+    }
+    /// asks every available server once, kiss codes update that server's state
+    pub fn poll(&mut self, client: &NtpClient) -> Vec<Sample> {
+        let now = Instant::now();
+        let mut samples = Vec::new();
+        for peer in self.peers.iter_mut().filter(|p| p.available(now)) {
+            match client.request(&peer.server) {
+                Ok(res) => {
+                    peer.answered();
+                    samples.push(Sample::new(&peer.server, &res));
+                }
+                Err(NtpError::KissOfDeath(code)) => {
+                    warn!("ntp server {} kissed: {}", peer.server, String::from_utf8_lossy(&code));
+                    peer.kiss(&code, now);
+                }
+                Err(e) => error!("ntp request {} failed! {}", peer.server, e),
+            }
+        }
+        samples
+    }
+}
@@ -0,0 +1,196 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc};
+use embedded_hal::i2c::I2c;
+
+use crate::net::source::{TimeSample, TimeSource, PRIORITY_RTC};
+
+/// fixed i2c address of the ds3231
+pub const DS3231_ADDRESS: u8 = 0x68;
+const REG_SECONDS: u8 = 0x00;
+const REG_STATUS: u8 = 0x0f;
+/// oscillator stopped, the time registers are not valid
+const STATUS_OSF: u8 = 0x80;
+const HOUR_12H: u8 = 0x40;
+const HOUR_PM: u8 = 0x20;
+const MONTH_CENTURY: u8 = 0x80;
+
+const fn from_bcd(value: u8) -> u32 {
+    ((value >> 4) * 10 + (value & 0x0f)) as u32
+}
+const fn to_bcd(value: u32) -> u8 {
+    (((value / 10) << 4) | (value % 10)) as u8
+}
+
+/// decodes the seven time registers starting at 0x00
+pub fn decode(regs: &[u8; 7]) -> Option<DateTime<Utc>> {
+    let hour = match regs[2] & HOUR_12H {
+        0 => from_bcd(regs[2] & 0x3f),
+        _ => from_bcd(regs[2] & 0x1f) % 12 + if regs[2] & HOUR_PM != 0 { 12 } else { 0 },
+    };
+    let time = NaiveTime::from_hms_opt(hour, from_bcd(regs[1] & 0x7f), from_bcd(regs[0] & 0x7f))?;
+    let century = if regs[5] & MONTH_CENTURY != 0 { 2100 } else { 2000 };
+    let date = NaiveDate::from_ymd_opt(
+        century + from_bcd(regs[6]) as i32,
+        from_bcd(regs[5] & 0x1f),
+        from_bcd(regs[4] & 0x3f),
+    )?;
+    Some(date.and_time(time).and_utc())
+}
+
+/// encodes a time into the seven time registers, always in 24 hour mode
+pub fn encode(utc: &DateTime<Utc>) -> [u8; 7] {
+    let century = if utc.year() >= 2100 { MONTH_CENTURY } else { 0 };
+    [
+        to_bcd(utc.second()),
+        to_bcd(utc.minute()),
+        to_bcd(utc.hour()),
+        to_bcd(utc.weekday().number_from_monday()),
+        to_bcd(utc.day()),
+        to_bcd(utc.month()) | century,
+        to_bcd(utc.year().rem_euclid(100) as u32),
+    ]
+}
+
+/// ds3231 real time clock on i2c, kept in utc
+pub struct RtcSource<I: I2c> {
+    i2c: I,
+    address: u8,
+}
+
+impl<I: I2c> RtcSource<I> {
+    pub fn new(i2c: I, address: u8) -> Self {
+        Self { i2c, address }
+    }
+    fn read(&mut self, reg: u8, buffer: &mut [u8]) -> anyhow::Result<()> {
+        self.i2c
+            .write_read(self.address, &[reg], buffer)
+            .map_err(|e| anyhow::Error::msg(format!("rtc read failed: {:?}", e)))
+    }
+}
+
+impl<I: I2c> TimeSource for RtcSource<I> {
+    fn name(&self) -> &str {
+        "rtc"
+    }
+    fn priority(&self) -> u8 {
+        PRIORITY_RTC
+    }
+    fn sample(&mut self) -> anyhow::Result<TimeSample> {
+        let mut status = [0u8];
+        self.read(REG_STATUS, &mut status)?;
+        if status[0] & STATUS_OSF != 0 {
+            return Err(anyhow::Error::msg("rtc oscillator stopped, time not set"));
+        }
+        let mut regs = [0u8; 7];
+        self.read(REG_SECONDS, &mut regs)?;
+        let local = SystemTime::now();
+        let utc = decode(&regs).ok_or(anyhow::Error::msg("rtc time invalid"))?;
+        // whole seconds only, the registers tick somewhere inside this second
+        Ok(TimeSample::at(self.name(), utc, local, Duration::seconds(1)))
+    }
+    fn set_time(&mut self, utc: &DateTime<Utc>) -> anyhow::Result<()> {
+        let mut buffer = [0u8; 8];
+        buffer[0] = REG_SECONDS;
+        buffer[1..].copy_from_slice(&encode(utc));
+        self.i2c
+            .write(self.address, &buffer)
+            .map_err(|e| anyhow::Error::msg(format!("rtc write failed: {:?}", e)))?;
+        let mut status = [0u8];
+        self.read(REG_STATUS, &mut status)?;
+        self.i2c
+            .write(self.address, &[REG_STATUS, status[0] & !STATUS_OSF])
+            .map_err(|e| anyhow::Error::msg(format!("rtc write failed: {:?}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, ErrorType, Operation};
+
+    /// register file of a ds3231 behind the i2c traits
+    struct FakeRtc {
+        address: u8,
+        regs: [u8; 0x13],
+    }
+
+    impl ErrorType for FakeRtc {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeRtc {
+        fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+            if address != self.address {
+                return Err(ErrorKind::Other);
+            }
+            let mut pointer = 0;
+            for operation in operations {
+                match operation {
+                    Operation::Write(bytes) => {
+                        pointer = bytes[0] as usize;
+                        for byte in &bytes[1..] {
+                            self.regs[pointer] = *byte;
+                            pointer += 1;
+                        }
+                    }
+                    Operation::Read(buffer) => {
+                        for byte in buffer.iter_mut() {
+                            *byte = self.regs[pointer];
+                            pointer += 1;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, s).unwrap().and_utc()
+    }
+
+    #[test]
+    fn bcd_round_trip() {
+        for time in [utc(2000, 1, 1, 0, 0, 0), utc(2024, 2, 29, 23, 59, 59), utc(2100, 12, 31, 12, 30, 45)] {
+            assert_eq!(decode(&encode(&time)), Some(time));
+        }
+        assert_eq!(encode(&utc(2024, 2, 29, 23, 59, 59)), [0x59, 0x59, 0x23, 0x04, 0x29, 0x02, 0x24]);
+    }
+
+    #[test]
+    fn twelve_hour_mode() {
+        // 12 AM is midnight, 12 PM noon
+        let mut regs = encode(&utc(2024, 5, 1, 0, 0, 0));
+        regs[2] = HOUR_12H | 0x12;
+        assert_eq!(decode(&regs), Some(utc(2024, 5, 1, 0, 0, 0)));
+        regs[2] = HOUR_12H | HOUR_PM | 0x12;
+        assert_eq!(decode(&regs), Some(utc(2024, 5, 1, 12, 0, 0)));
+        regs[2] = HOUR_12H | HOUR_PM | 0x07;
+        assert_eq!(decode(&regs), Some(utc(2024, 5, 1, 19, 0, 0)));
+    }
+
+    #[test]
+    fn invalid_registers() {
+        let mut regs = encode(&utc(2023, 2, 28, 0, 0, 0));
+        regs[4] = 0x30;
+        assert_eq!(decode(&regs), None);
+    }
+
+    #[test]
+    fn source_on_configured_address() {
+        let mut rtc = FakeRtc { address: 0x57, regs: [0; 0x13] };
+        rtc.regs[REG_STATUS as usize] = STATUS_OSF;
+        let mut source = RtcSource::new(rtc, 0x57);
+        assert!(source.sample().is_err());
+
+        let time = utc(2030, 1, 1, 0, 0, 0);
+        source.set_time(&time).unwrap();
+        assert_eq!(source.i2c.regs[REG_STATUS as usize] & STATUS_OSF, 0);
+        let sample = source.sample().unwrap();
+        assert!((sample.offset - (time - Utc::now())).num_seconds().abs() <= 1);
+
+        let mut elsewhere = RtcSource::new(FakeRtc { address: DS3231_ADDRESS, regs: [0; 0x13] }, 0x57);
+        assert!(elsewhere.sample().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use std::net::SocketAddr;
+
+use chrono::Duration;
+
+use crate::net::ntp::{Leap, Response};
+
+/// one server's answer reduced to what the selection needs
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub server: String,
+    pub offset: Duration,
+    pub delay: Duration,
+    pub dispersion: Duration,
+    pub leap: Leap,
+    pub stratum: u8,
+    pub root_delay: Duration,
+    pub peer: Option<SocketAddr>,
+}
+
+impl Sample {
+    pub fn new(server: &str, response: &Response) -> Self {
+        Self {
+            server: server.into(),
+            offset: response.offset,
+            // a server clock stepping mid exchange can give less than nothing
+            delay: response.delay.max(Duration::zero()),
+            dispersion: response.dispersion(),
+            leap: response.packet.leap(),
+            stratum: response.packet.stratum,
+            root_delay: response.packet.root_delay(),
+            peer: response.peer,
+        }
+    }
+    /// root synchronization distance, half of the correctness interval
+    pub fn distance(&self) -> Duration {
+        self.delay / 2 + self.dispersion
+    }
+    /// the true offset is somewhere in `offset ± distance`
+    pub fn interval(&self) -> (Duration, Duration) {
+        (self.offset - self.distance(), self.offset + self.distance())
+    }
+}
+
+/// Marzullo's algorithm, the smallest interval shared by the most samples.
+/// returns `(low, high, count)` only when a strict majority agrees.
+pub fn intersect(samples: &[Sample]) -> Option<(Duration, Duration, usize)> {
+    // lower edges sort before upper edges at the same offset so touching intervals still agree
+    let mut edges = samples
+        .iter()
+        // an inverted interval agrees with nothing, it still counts against the majority
+        .filter(|s| s.distance() >= Duration::zero())
+        .flat_map(|s| {
+            let (low, high) = s.interval();
+            [(low, -1), (high, 1)]
+        })
+        .collect::<Vec<(Duration, i32)>>();
+    edges.sort();
+    let (mut best, mut count) = (0i32, 0i32);
+    let mut found = None;
+    for (i, (edge, kind)) in edges.iter().enumerate() {
+        count -= kind;
+        if count > best {
+            best = count;
+            found = edges.get(i + 1).map(|(next, _)| (*edge, *next));
+        }
+    }
+    let (low, high) = found?;
+    match best as usize * 2 > samples.len() {
+        true => Some((low, high, best as usize)),
+        false => None,
+    }
+}
+
+/// drop the falsetickers, then pick the survivor with the smallest distance
+pub fn select(samples: &[Sample]) -> Option<&Sample> {
+    let (low, high, _) = intersect(samples)?;
+    samples
+        .iter()
+        .filter(|s| {
+            let (l, h) = s.interval();
+            l <= high && h >= low
+        })
+        .min_by_key(|s| s.distance())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::ntp::{NtpTimestamp, Packet};
+
+    fn sample(server: &str, offset: i64, delay: i64, dispersion: i64) -> Sample {
+        Sample {
+            server: server.into(),
+            offset: Duration::milliseconds(offset),
+            delay: Duration::milliseconds(delay),
+            dispersion: Duration::milliseconds(dispersion),
+            leap: Leap::None,
+            stratum: 2,
+            root_delay: Duration::zero(),
+            peer: None,
+        }
+    }
+
+    #[test]
+    fn falseticker_is_dropped() {
+        let samples = [sample("a", 100, 20, 5), sample("b", 110, 40, 5), sample("c", 5000, 10, 5)];
+        let (low, high, count) = intersect(&samples).unwrap();
+        assert_eq!((low.num_milliseconds(), high.num_milliseconds(), count), (85, 115, 2));
+        assert_eq!(select(&samples).unwrap().server, "a");
+    }
+
+    #[test]
+    fn touching_intervals_agree() {
+        let samples = [sample("a", 0, 0, 10), sample("b", 20, 0, 10)];
+        assert_eq!(intersect(&samples).map(|(_, _, count)| count), Some(2));
+    }
+
+    #[test]
+    fn no_majority() {
+        let samples = [sample("a", 0, 10, 5), sample("b", 1000, 10, 5)];
+        assert!(intersect(&samples).is_none());
+        assert!(select(&samples).is_none());
+        assert!(select(&[]).is_none());
+    }
+
+    #[test]
+    fn negative_delay_does_not_wrap() {
+        // the interval comes out inverted, it must count as agreeing with nothing
+        let samples = [sample("a", 0, -100, 5), sample("b", 0, 10, 5), sample("c", 3, 10, 5)];
+        assert_eq!(intersect(&samples).map(|(_, _, count)| count), Some(2));
+        let samples = [sample("a", 0, -100, 5)];
+        assert!(intersect(&samples).is_none());
+    }
+
+    #[test]
+    fn negative_delay_is_clamped() {
+        let packet = Packet {
+            originate: NtpTimestamp { seconds: 100, fraction: 0 },
+            receive: NtpTimestamp { seconds: 101, fraction: 0 },
+            transmit: NtpTimestamp { seconds: 103, fraction: 0 },
+            ..Default::default()
+        };
+        let response = Response::new(packet, NtpTimestamp { seconds: 101, fraction: 0 }, 0);
+        assert!(response.delay < Duration::zero());
+        assert_eq!(Sample::new("a", &response).delay, Duration::zero());
+    }
+}
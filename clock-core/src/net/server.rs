@@ -0,0 +1,188 @@
+use std::net::{ToSocketAddrs, UdpSocket};
+
+use chrono::Duration;
+use log::error;
+
+use crate::net::ntp::{duration_to_short, Leap, NtpError, NtpTimestamp, Packet, MODE_CLIENT, MODE_SERVER, PACKET_SIZE};
+use crate::net::source::TimeSample;
+
+/// announced by a server that has no usable time
+const STRATUM_UNSYNCHRONIZED: u8 = 16;
+/// the clock is only ever set to a millisecond
+const PRECISION: i8 = -10;
+/// frequency tolerance, 15 ppm as in RFC 5905
+const PHI_PPM: i32 = 15;
+
+/// what we know about our own time, announced to the clients
+#[derive(Debug, Clone, Copy)]
+pub struct Reference {
+    pub leap: Leap,
+    pub stratum: u8,
+    pub reference_id: [u8; 4],
+    /// when the clock was last set
+    pub time: NtpTimestamp,
+    pub root_delay: Duration,
+    pub root_dispersion: Duration,
+}
+
+impl Reference {
+    /// one stratum below the source the clock was set from
+    pub fn new(sample: &TimeSample, time: NtpTimestamp) -> Self {
+        Self {
+            leap: sample.leap,
+            stratum: sample.stratum.saturating_add(1).min(STRATUM_UNSYNCHRONIZED),
+            reference_id: sample.reference_id,
+            time,
+            root_delay: sample.delay,
+            root_dispersion: sample.accuracy,
+        }
+    }
+    /// dispersion keeps growing with the time since the last sync
+    pub fn root_dispersion_at(&self, now: &NtpTimestamp) -> Duration {
+        // whole seconds of the wrapping 32.32 difference, fine across an era boundary
+        let elapsed = Duration::seconds((now.as_u64().wrapping_sub(self.time.as_u64()) as i64) >> 32);
+        self.root_dispersion + elapsed * PHI_PPM / 1_000_000
+    }
+}
+
+/// builds the answer to a client request, `None` for anything that is not a client request.
+/// without a reference the answer says unsynchronized so clients will not use it
+pub fn answer(request: &Packet, receive: NtpTimestamp, reference: Option<&Reference>) -> Option<Packet> {
+    if request.mode() != MODE_CLIENT || !(1..=4).contains(&request.version()) {
+        return None;
+    }
+    let mut reply = Packet {
+        poll: request.poll,
+        precision: PRECISION,
+        originate: request.transmit,
+        receive,
+        ..Default::default()
+    };
+    let leap = match reference {
+        None => {
+            reply.stratum = STRATUM_UNSYNCHRONIZED;
+            Leap::Unsynchronized
+        }
+        Some(reference) => {
+            reply.stratum = reference.stratum;
+            reply.reference_id = reference.reference_id;
+            reply.reference = reference.time;
+            reply.root_delay = duration_to_short(reference.root_delay);
+            reply.root_dispersion = duration_to_short(reference.root_dispersion_at(&receive));
+            reference.leap
+        }
+    };
+    reply.li_vn_mode = u8::from(leap) << 6 | request.version() << 3 | MODE_SERVER;
+    Some(reply)
+}
+
+pub struct NtpServer {
+    socket: UdpSocket,
+}
+
+impl NtpServer {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self, NtpError> {
+        Ok(Self { socket: UdpSocket::bind(addr)? })
+    }
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+    /// answers one request, `reference` is asked for our current state every time
+    pub fn serve_one(&self, reference: impl Fn() -> Option<Reference>) -> Result<(), NtpError> {
+        let mut buf = [0u8; PACKET_SIZE];
+        let (size, peer) = self.socket.recv_from(&mut buf)?;
+        let receive = NtpTimestamp::now();
+        if size < PACKET_SIZE {
+            return Err(NtpError::ShortPacket(size));
+        }
+        let request = Packet::unpack(&buf)?;
+        if let Some(mut reply) = answer(&request, receive, reference().as_ref()) {
+            reply.transmit = NtpTimestamp::now();
+            self.socket.send_to(&reply.pack(), peer)?;
+        }
+        Ok(())
+    }
+    pub fn serve(&self, reference: impl Fn() -> Option<Reference>) -> ! {
+        loop {
+            if let Err(e) = self.serve_one(&reference) {
+                error!("sntp server: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration as StdDuration;
+
+    fn reference() -> Reference {
+        Reference {
+            leap: Leap::None,
+            stratum: 2,
+            reference_id: *b"GPS\0",
+            time: NtpTimestamp::now(),
+            root_delay: Duration::milliseconds(30),
+            root_dispersion: Duration::milliseconds(10),
+        }
+    }
+
+    /// sends one client request to a server answering on loopback and hands back the reply
+    fn exchange(request: Packet, reference: impl Fn() -> Option<Reference> + Send + 'static) -> (Packet, u64, u64) {
+        let server = NtpServer::bind("127.0.0.1:0").unwrap();
+        let address = server.socket().local_addr().unwrap();
+        let handle = std::thread::spawn(move || server.serve_one(reference).unwrap());
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client.set_read_timeout(Some(StdDuration::from_secs(2))).unwrap();
+        let before = NtpTimestamp::now().as_u64();
+        client.send_to(&request.pack(), address).unwrap();
+        let mut buf = [0u8; PACKET_SIZE];
+        client.recv_from(&mut buf).unwrap();
+        let after = NtpTimestamp::now().as_u64();
+        handle.join().unwrap();
+        (Packet::unpack(&buf).unwrap(), before, after)
+    }
+
+    #[test]
+    fn answers_loopback_client() {
+        let origin = NtpTimestamp { seconds: 0x1234_5678, fraction: 0x9abc_def0 };
+        let (reply, before, after) = exchange(Packet::request(origin), || Some(reference()));
+        assert_eq!(reply.mode(), MODE_SERVER);
+        assert_eq!(reply.version(), 4);
+        assert_eq!(reply.leap(), Leap::None);
+        assert_eq!(reply.stratum, 2);
+        assert_eq!(&reply.reference_id, b"GPS\0");
+        assert_eq!(reply.originate, origin);
+        assert_eq!(reply.root_delay, duration_to_short(Duration::milliseconds(30)));
+        // received and sent while the client waited, in that order
+        let (receive, transmit) = (reply.receive.as_u64(), reply.transmit.as_u64());
+        assert!(before <= receive && receive <= transmit && transmit <= after);
+        assert!(reply.validate().is_ok());
+    }
+
+    #[test]
+    fn unsynchronized_until_first_sync() {
+        let (reply, _, _) = exchange(Packet::request(NtpTimestamp::now()), || None);
+        assert_eq!(reply.mode(), MODE_SERVER);
+        assert_eq!(reply.leap(), Leap::Unsynchronized);
+        assert_eq!(reply.stratum, STRATUM_UNSYNCHRONIZED);
+        assert!(reply.validate().is_err());
+    }
+
+    #[test]
+    fn ignores_anything_but_client_requests() {
+        let mut request = Packet::request(NtpTimestamp::now());
+        request.li_vn_mode = (4 << 3) | MODE_SERVER;
+        assert!(answer(&request, NtpTimestamp::now(), Some(&reference())).is_none());
+        request.li_vn_mode = (5 << 3) | MODE_CLIENT;
+        assert!(answer(&request, NtpTimestamp::now(), Some(&reference())).is_none());
+    }
+
+    #[test]
+    fn dispersion_grows_since_sync() {
+        let reference = reference();
+        let later = NtpTimestamp { seconds: reference.time.seconds.wrapping_add(1000), ..reference.time };
+        // 15 ppm of 1000 s
+        assert_eq!(reference.root_dispersion_at(&later).num_milliseconds(), 25);
+    }
+}
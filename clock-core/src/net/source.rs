@@ -0,0 +1,359 @@
+use std::net::IpAddr;
+use std::time::{Instant, SystemTime};
+
+use chrono::{DateTime, Duration, Utc};
+use log::error;
+
+use crate::net::ntp::{Leap, NtpClient, NtpError};
+use crate::net::peer::Peers;
+use crate::net::select::select;
+
+/// sources are asked in this order, lower first
+pub const PRIORITY_GPS: u8 = 0;
+pub const PRIORITY_NTP: u8 = 1;
+pub const PRIORITY_HTTP: u8 = 2;
+pub const PRIORITY_RTC: u8 = 3;
+pub const PRIORITY_MANUAL: u8 = 4;
+
+/// stratum of sources that are not traceable to a reference clock,
+/// the sntp server announces itself unsynchronized when set from them
+pub const STRATUM_UNTRACEABLE: u8 = 15;
+
+/// one reading of a time source
+#[derive(Debug, Clone)]
+pub struct TimeSample {
+    pub source: String,
+    /// correction to add to the local clock
+    pub offset: Duration,
+    /// the corrected clock is within `offset ± accuracy`
+    pub accuracy: Duration,
+    pub leap: Leap,
+    /// stratum of the source itself, 0 for a reference clock
+    pub stratum: u8,
+    pub reference_id: [u8; 4],
+    /// total round trip delay to the reference clock
+    pub delay: Duration,
+}
+
+impl TimeSample {
+    /// a reading taken from an absolute time, `local` is the local clock at the same instant
+    pub fn at(source: &str, time: DateTime<Utc>, local: SystemTime, accuracy: Duration) -> Self {
+        Self {
+            source: source.into(),
+            offset: time - DateTime::<Utc>::from(local),
+            accuracy,
+            leap: Leap::None,
+            stratum: STRATUM_UNTRACEABLE,
+            reference_id: [0; 4],
+            delay: Duration::zero(),
+        }
+    }
+}
+
+pub trait TimeSource {
+    fn name(&self) -> &str;
+    /// lower is asked first, see the `PRIORITY_*` constants
+    fn priority(&self) -> u8;
+    /// whether asking now makes sense at all
+    fn available(&self) -> bool {
+        true
+    }
+    fn sample(&mut self) -> anyhow::Result<TimeSample>;
+    /// called with the corrected time after another source set the clock,
+    /// sources that keep time themselves (a rtc) can follow it
+    fn set_time(&mut self, _utc: &DateTime<Utc>) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// asks the available sources in priority order, the first priority level
+/// that answers wins, within a level the most accurate answer is used
+#[derive(Default)]
+pub struct Scheduler {
+    sources: Vec<Box<dyn TimeSource + Send>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn add(&mut self, source: Box<dyn TimeSource + Send>) {
+        self.sources.push(source);
+        self.sources.sort_by_key(|s| s.priority());
+    }
+    pub fn sample(&mut self) -> anyhow::Result<TimeSample> {
+        let mut best: Option<(u8, TimeSample)> = None;
+        let mut last_error = None;
+        for source in self.sources.iter_mut().filter(|s| s.available()) {
+            if matches!(best, Some((priority, _)) if priority < source.priority()) {
+                break;
+            }
+            match source.sample() {
+                Ok(sample) => {
+                    if best.as_ref().is_none_or(|(_, b)| sample.accuracy < b.accuracy) {
+                        best = Some((source.priority(), sample));
+                    }
+                }
+                Err(e) => {
+                    error!("time source {} failed! {}", source.name(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        match best {
+            Some((_, sample)) => Ok(sample),
+            None => Err(last_error.unwrap_or(anyhow::Error::msg("no time source available"))),
+        }
+    }
+    /// passes the new time to every source except the one it came from
+    pub fn set_time(&mut self, from: &TimeSample, utc: &DateTime<Utc>) {
+        for source in self.sources.iter_mut() {
+            if source.name() == from.source {
+                continue;
+            }
+            if let Err(e) = source.set_time(utc) {
+                error!("time source {} set failed! {}", source.name(), e);
+            }
+        }
+    }
+}
+
+pub struct NtpSource {
+    peers: Peers,
+    client: NtpClient,
+}
+
+impl NtpSource {
+    pub fn new(peers: Peers, client: NtpClient) -> Self {
+        Self { peers, client }
+    }
+}
+
+impl TimeSource for NtpSource {
+    fn name(&self) -> &str {
+        "ntp"
+    }
+    fn priority(&self) -> u8 {
+        PRIORITY_NTP
+    }
+    fn sample(&mut self) -> anyhow::Result<TimeSample> {
+        let samples = self.peers.poll(&self.client);
+        if samples.is_empty() {
+            return Err(NtpError::Unreachable.into());
+        }
+        let best = select(&samples).ok_or(NtpError::NoMajority)?;
+        let reference_id = match best.peer.map(|peer| peer.ip()) {
+            Some(IpAddr::V4(ip)) => ip.octets(),
+            _ => [0; 4],
+        };
+        Ok(TimeSample {
+            source: self.name().into(),
+            offset: best.offset,
+            accuracy: best.distance(),
+            leap: best.leap,
+            stratum: best.stratum,
+            reference_id,
+            delay: best.root_delay + best.delay,
+        })
+    }
+}
+
+/// a time entered by hand, used once and dropped as soon as a better source sets the clock
+pub struct ManualSource {
+    entered: Option<(DateTime<Utc>, Instant)>,
+    accuracy: Duration,
+}
+
+impl ManualSource {
+    pub fn new(accuracy: Duration) -> Self {
+        Self { entered: None, accuracy }
+    }
+    pub fn enter(&mut self, utc: DateTime<Utc>) {
+        self.entered = Some((utc, Instant::now()));
+    }
+}
+
+impl TimeSource for ManualSource {
+    fn name(&self) -> &str {
+        "manual"
+    }
+    fn priority(&self) -> u8 {
+        PRIORITY_MANUAL
+    }
+    fn available(&self) -> bool {
+        self.entered.is_some()
+    }
+    fn sample(&mut self) -> anyhow::Result<TimeSample> {
+        let (utc, at) = self.entered.take().ok_or(anyhow::Error::msg("no time entered"))?;
+        let elapsed = Duration::from_std(at.elapsed())?;
+        Ok(TimeSample::at(self.name(), utc + elapsed, SystemTime::now(), self.accuracy))
+    }
+    fn set_time(&mut self, _utc: &DateTime<Utc>) -> anyhow::Result<()> {
+        // the entered time is older than the one just set, falling back to it later would step the clock back
+        self.entered = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    type Log<T> = Arc<Mutex<Vec<T>>>;
+
+    /// answers with a fixed offset and accuracy, or fails, and records what it is asked
+    struct FakeSource {
+        name: &'static str,
+        priority: u8,
+        available: bool,
+        /// offset and accuracy in milliseconds, `None` fails
+        reading: Option<(i64, i64)>,
+        sampled: Log<&'static str>,
+        set: Log<(&'static str, DateTime<Utc>)>,
+    }
+
+    impl TimeSource for FakeSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+        fn priority(&self) -> u8 {
+            self.priority
+        }
+        fn available(&self) -> bool {
+            self.available
+        }
+        fn sample(&mut self) -> anyhow::Result<TimeSample> {
+            self.sampled.lock().unwrap().push(self.name);
+            let (offset, accuracy) = self.reading.ok_or(anyhow::Error::msg("no reading"))?;
+            Ok(TimeSample {
+                source: self.name.into(),
+                offset: Duration::milliseconds(offset),
+                accuracy: Duration::milliseconds(accuracy),
+                leap: Leap::None,
+                stratum: 1,
+                reference_id: [0; 4],
+                delay: Duration::zero(),
+            })
+        }
+        fn set_time(&mut self, utc: &DateTime<Utc>) -> anyhow::Result<()> {
+            self.set.lock().unwrap().push((self.name, *utc));
+            match self.reading {
+                Some(_) => Ok(()),
+                None => Err(anyhow::Error::msg("read only")),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Fakes {
+        scheduler: Scheduler,
+        sampled: Log<&'static str>,
+        set: Log<(&'static str, DateTime<Utc>)>,
+    }
+
+    impl Fakes {
+        fn add(&mut self, name: &'static str, priority: u8, available: bool, reading: Option<(i64, i64)>) {
+            self.scheduler.add(Box::new(FakeSource {
+                name,
+                priority,
+                available,
+                reading,
+                sampled: self.sampled.clone(),
+                set: self.set.clone(),
+            }));
+        }
+        fn sampled(&self) -> Vec<&'static str> {
+            self.sampled.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn first_priority_that_answers_wins() {
+        let mut fakes = Fakes::default();
+        fakes.add("manual", PRIORITY_MANUAL, true, Some((4, 1)));
+        fakes.add("ntp", PRIORITY_NTP, true, Some((1, 50)));
+        fakes.add("http", PRIORITY_HTTP, true, Some((2, 500)));
+        assert_eq!(fakes.scheduler.sample().unwrap().source, "ntp");
+        assert_eq!(fakes.sampled(), ["ntp"]);
+    }
+
+    #[test]
+    fn falls_back_to_lower_priority() {
+        let mut fakes = Fakes::default();
+        fakes.add("gps", PRIORITY_GPS, false, Some((0, 1)));
+        fakes.add("ntp", PRIORITY_NTP, true, None);
+        fakes.add("http", PRIORITY_HTTP, true, Some((2, 500)));
+        fakes.add("rtc", PRIORITY_RTC, true, Some((3, 1000)));
+        assert_eq!(fakes.scheduler.sample().unwrap().source, "http");
+        assert_eq!(fakes.sampled(), ["ntp", "http"]);
+    }
+
+    #[test]
+    fn most_accurate_within_priority() {
+        let mut fakes = Fakes::default();
+        fakes.add("far", PRIORITY_NTP, true, Some((10, 80)));
+        fakes.add("near", PRIORITY_NTP, true, Some((12, 20)));
+        fakes.add("same", PRIORITY_NTP, true, Some((14, 20)));
+        let sample = fakes.scheduler.sample().unwrap();
+        // a tie keeps the first answer
+        assert_eq!((sample.source.as_str(), sample.offset.num_milliseconds()), ("near", 12));
+    }
+
+    #[test]
+    fn last_error_when_nothing_answers() {
+        let mut fakes = Fakes::default();
+        assert!(fakes.scheduler.sample().is_err());
+        fakes.add("ntp", PRIORITY_NTP, true, None);
+        fakes.add("rtc", PRIORITY_RTC, false, Some((0, 1)));
+        assert_eq!(fakes.scheduler.sample().unwrap_err().to_string(), "no reading");
+    }
+
+    #[test]
+    fn set_time_reaches_every_other_source() {
+        let mut fakes = Fakes::default();
+        fakes.add("ntp", PRIORITY_NTP, true, Some((0, 10)));
+        fakes.add("http", PRIORITY_HTTP, true, None);
+        fakes.add("rtc", PRIORITY_RTC, false, Some((0, 1000)));
+        let sample = fakes.scheduler.sample().unwrap();
+        let now = Utc::now();
+        fakes.scheduler.set_time(&sample, &now);
+        // a failing source does not stop the others
+        assert_eq!(*fakes.set.lock().unwrap(), [("http", now), ("rtc", now)]);
+    }
+
+    #[test]
+    fn manual_time_is_dropped_once_synced() {
+        let mut fakes = Fakes::default();
+        fakes.add("ntp", PRIORITY_NTP, true, Some((0, 10)));
+        let mut manual = ManualSource::new(Duration::seconds(30));
+        manual.enter(Utc::now() - Duration::days(30));
+        fakes.scheduler.add(Box::new(manual));
+        let sample = fakes.scheduler.sample().unwrap();
+        fakes.scheduler.set_time(&sample, &Utc::now());
+        // ntp failing later leaves nothing to fall back to
+        fakes.scheduler.sources[0] = Box::new(FakeSource {
+            name: "ntp",
+            priority: PRIORITY_NTP,
+            available: true,
+            reading: None,
+            sampled: fakes.sampled.clone(),
+            set: fakes.set.clone(),
+        });
+        assert_eq!(fakes.scheduler.sample().unwrap_err().to_string(), "no reading");
+    }
+
+    #[test]
+    fn manual_time_is_used_once() {
+        let mut fakes = Fakes::default();
+        let mut manual = ManualSource::new(Duration::seconds(30));
+        let entered = Utc::now() + Duration::hours(1);
+        manual.enter(entered);
+        fakes.scheduler.add(Box::new(manual));
+        let sample = fakes.scheduler.sample().unwrap();
+        assert_eq!(sample.source, "manual");
+        assert!((sample.offset - Duration::hours(1)).num_seconds().abs() <= 1);
+        assert!(fakes.scheduler.sample().is_err());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod player;
+pub mod rtttl;
@@ -0,0 +1,229 @@
+use std::time::{Duration, Instant};
+
+use log::error;
+
+use crate::sound::rtttl::Note;
+
+/// silence at the end of each note, so the same note twice sounds as two
+const GAP: Duration = Duration::from_millis(10);
+/// silence before a repeating melody starts over
+const REPEAT_PAUSE: Duration = Duration::from_secs(1);
+
+/// something that sounds one frequency at a time
+pub trait ToneOutput {
+    /// `None` silences it
+    fn tone(&mut self, frequency: Option<u32>) -> anyhow::Result<()>;
+}
+
+/// steps through a melody as time goes by, the time comes from the caller so a fake output
+/// on the host can record what would be heard
+pub struct Player<T: ToneOutput> {
+    output: T,
+    notes: Vec<Note>,
+    repeat: bool,
+    /// each note is two steps, the note and its gap, a repeating melody has the pause after them
+    step: usize,
+    /// when the current step ends, `None` while nothing plays
+    ends: Option<Instant>,
+    sounding: Option<u32>,
+}
+
+impl<T: ToneOutput> Player<T> {
+    pub fn new(output: T) -> Self {
+        Self { output, notes: Vec::new(), repeat: false, step: 0, ends: None, sounding: None }
+    }
+
+    pub fn output(&self) -> &T {
+        &self.output
+    }
+
+    pub fn playing(&self) -> bool {
+        self.ends.is_some()
+    }
+
+    fn steps(&self) -> usize {
+        self.notes.len() * 2 + self.repeat as usize
+    }
+
+    fn step(&self, step: usize) -> (Option<u32>, Duration) {
+        match self.notes.get(step / 2) {
+            Some(note) => {
+                let gap = match note.frequency.is_some() && note.duration > GAP * 2 {
+                    true => GAP,
+                    false => Duration::ZERO,
+                };
+                match step % 2 {
+                    0 => (note.frequency, note.duration - gap),
+                    _ => (None, gap),
+                }
+            }
+            None => (None, REPEAT_PAUSE),
+        }
+    }
+
+    fn sound(&mut self, frequency: Option<u32>) {
+        if frequency != self.sounding {
+            self.sounding = frequency;
+            if let Err(e) = self.output.tone(frequency) {
+                error!("tone output failed: {}", e);
+            }
+        }
+    }
+
+    /// starts `notes` over whatever plays, `repeat` plays them until stopped
+    pub fn play(&mut self, notes: Vec<Note>, repeat: bool, now: Instant) {
+        self.notes = notes;
+        self.repeat = repeat && !self.notes.is_empty();
+        self.step = 0;
+        match self.notes.is_empty() {
+            true => self.stop(),
+            false => {
+                let (frequency, duration) = self.step(0);
+                self.ends = Some(now + duration);
+                self.sound(frequency);
+                self.poll(now);
+            }
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.ends = None;
+        self.sound(None);
+    }
+
+    /// moves on to the step sounding at `now` and tells when the next one starts, steps are
+    /// timed from the start of the melody so a late poll skips ahead rather than dragging it
+    pub fn poll(&mut self, now: Instant) -> Option<Instant> {
+        let mut ends = self.ends?;
+        while ends <= now {
+            self.step += 1;
+            if self.step == self.steps() {
+                match self.repeat {
+                    true => self.step = 0,
+                    false => {
+                        self.stop();
+                        return None;
+                    }
+                }
+            }
+            ends += self.step(self.step).1;
+        }
+        self.ends = Some(ends);
+        self.sound(self.step(self.step).0);
+        Some(ends)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::sound::rtttl::parse;
+
+    /// every change of tone with the time it happened, relative to the start
+    struct RecordingOutput {
+        clock: Rc<Cell<Instant>>,
+        start: Instant,
+        calls: Vec<(Instant, Option<u32>)>,
+    }
+
+    impl ToneOutput for RecordingOutput {
+        fn tone(&mut self, frequency: Option<u32>) -> anyhow::Result<()> {
+            self.calls.push((self.clock.get(), frequency));
+            Ok(())
+        }
+    }
+
+    impl RecordingOutput {
+        fn millis(&self) -> Vec<(u64, Option<u32>)> {
+            self.calls.iter().map(|(at, frequency)| ((*at - self.start).as_millis() as u64, *frequency)).collect()
+        }
+    }
+
+    fn player() -> (Player<RecordingOutput>, Rc<Cell<Instant>>) {
+        let clock = Rc::new(Cell::new(Instant::now()));
+        let output = RecordingOutput { clock: clock.clone(), start: clock.get(), calls: Vec::new() };
+        (Player::new(output), clock)
+    }
+
+    /// polls every time the player asks to, up to `millis` after the start
+    fn run(player: &mut Player<RecordingOutput>, clock: &Cell<Instant>, millis: u64) {
+        let until = player.output().start + Duration::from_millis(millis);
+        while let Some(next) = player.poll(clock.get()) {
+            if next > until {
+                break;
+            }
+            clock.set(next);
+        }
+    }
+
+    const C5: Option<u32> = Some(523);
+    const D5: Option<u32> = Some(587);
+    const E5: Option<u32> = Some(659);
+
+    #[test]
+    fn gap_between_notes() {
+        // quarter notes of 1 s, the same note twice is heard as two
+        let (mut player, clock) = player();
+        player.play(parse("x:d=4,o=5,b=60:c,c,p,d").unwrap(), false, clock.get());
+        run(&mut player, &clock, 10_000);
+        assert_eq!(player.output().millis(), vec![
+            (0, C5), (990, None), (1000, C5), (1990, None), (3000, D5), (3990, None),
+        ]);
+        assert!(!player.playing());
+    }
+
+    #[test]
+    fn dotted_notes() {
+        let (mut player, clock) = player();
+        player.play(parse("x:d=4,o=5,b=60:c.,8d.,e").unwrap(), false, clock.get());
+        run(&mut player, &clock, 10_000);
+        assert_eq!(player.output().millis(), vec![
+            (0, C5), (1490, None), (1500, D5), (2240, None), (2250, E5), (3240, None),
+        ]);
+    }
+
+    #[test]
+    fn repeat_pauses_between_rounds() {
+        let (mut player, clock) = player();
+        player.play(parse("x:d=4,o=5,b=60:c,d").unwrap(), true, clock.get());
+        run(&mut player, &clock, 6_500);
+        assert_eq!(player.output().millis(), vec![
+            (0, C5), (990, None), (1000, D5), (1990, None),
+            (3000, C5), (3990, None), (4000, D5), (4990, None),
+            (6000, C5),
+        ]);
+        assert!(player.playing());
+        player.stop();
+        assert!(!player.playing());
+        assert_eq!(player.output().calls.last().map(|call| call.1), Some(None));
+    }
+
+    #[test]
+    fn late_poll_skips_ahead() {
+        let (mut player, clock) = player();
+        player.play(parse("x:d=4,o=5,b=60:c,d,e").unwrap(), false, clock.get());
+        // the thread was held up for 2.5 s, d is skipped and e still ends on time
+        clock.set(clock.get() + Duration::from_millis(2500));
+        let next = player.poll(clock.get()).unwrap();
+        assert_eq!((next - player.output().start).as_millis(), 2990);
+        assert_eq!(player.output().millis(), vec![(0, C5), (2500, E5)]);
+        clock.set(clock.get() + Duration::from_secs(5));
+        assert_eq!(player.poll(clock.get()), None);
+        assert_eq!(player.output().millis().last(), Some(&(7500, None)));
+    }
+
+    #[test]
+    fn play_over_a_melody() {
+        let (mut player, clock) = player();
+        player.play(parse("x:d=4,o=5,b=60:c").unwrap(), true, clock.get());
+        clock.set(clock.get() + Duration::from_millis(500));
+        player.play(parse("x:d=4,o=5,b=60:e").unwrap(), false, clock.get());
+        run(&mut player, &clock, 10_000);
+        assert_eq!(player.output().millis(), vec![(0, C5), (500, E5), (1490, None)]);
+        player.play(Vec::new(), true, clock.get());
+        assert!(!player.playing());
+    }
+}
@@ -0,0 +1,145 @@
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail};
+
+/// defaults of the RTTTL spec, used for whatever the second section leaves out
+const DEFAULT_DURATION: u32 = 4;
+const DEFAULT_OCTAVE: u32 = 6;
+const DEFAULT_BPM: u32 = 63;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// hertz, `None` for a rest
+    pub frequency: Option<u32>,
+    pub duration: Duration,
+}
+
+/// frequency of `semitone` steps above C in `octave`, A4 is 440 Hz
+fn frequency(semitone: u32, octave: u32) -> u32 {
+    let from_a4 = (octave * 12 + semitone) as f32 - 57.0;
+    (440.0 * 2f32.powf(from_a4 / 12.0) + 0.5) as u32
+}
+
+fn number(value: &str, what: &str) -> anyhow::Result<u32> {
+    value.trim().parse().map_err(|_| anyhow!("invalid {} {:?}", what, value))
+}
+
+/// the number at the front of `chars`, `None` without one
+fn digits(chars: &mut Peekable<Chars>, note: &str) -> anyhow::Result<Option<u32>> {
+    let mut value = None;
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        value = Some(value.unwrap_or(0u32)
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(digit))
+            .ok_or(anyhow!("number too large in {:?}", note))?);
+        chars.next();
+    }
+    Ok(value)
+}
+
+/// `name:d=4,o=5,b=120:8c6,8p,4e.,g#` into the notes it plays, the name is dropped
+pub fn parse(text: &str) -> anyhow::Result<Vec<Note>> {
+    let mut sections = text.splitn(3, ':');
+    let (_name, defaults, notes) = match (sections.next(), sections.next(), sections.next()) {
+        (Some(name), Some(defaults), Some(notes)) => (name, defaults, notes),
+        _ => bail!("ringtone needs name, defaults and notes separated by ':'"),
+    };
+    let (mut duration, mut octave, mut bpm) = (DEFAULT_DURATION, DEFAULT_OCTAVE, DEFAULT_BPM);
+    for setting in defaults.split(',').map(str::trim).filter(|setting| !setting.is_empty()) {
+        let (key, value) = setting.split_once('=').ok_or(anyhow!("invalid default {:?}", setting))?;
+        match key.trim().to_ascii_lowercase().as_str() {
+            "d" => duration = number(value, "duration")?,
+            "o" => octave = number(value, "octave")?,
+            "b" => bpm = number(value, "bpm")?,
+            _ => bail!("unknown default {:?}", setting),
+        }
+    }
+    if !(1..=900).contains(&bpm) {
+        bail!("bpm {} out of range", bpm);
+    }
+    // a whole note is four beats
+    let whole = 240_000 / bpm;
+    notes
+        .split(',')
+        .map(str::trim)
+        .filter(|note| !note.is_empty())
+        .map(|note| {
+            let lower = note.to_ascii_lowercase();
+            let mut chars = lower.chars().peekable();
+            let length = digits(&mut chars, note)?.unwrap_or(duration);
+            if ![1, 2, 4, 8, 16, 32, 64].contains(&length) {
+                bail!("invalid duration in {:?}", note);
+            }
+            let semitone = match chars.next() {
+                Some('c') => Some(0),
+                Some('d') => Some(2),
+                Some('e') => Some(4),
+                Some('f') => Some(5),
+                Some('g') => Some(7),
+                Some('a') => Some(9),
+                Some('b') | Some('h') => Some(11),
+                Some('p') => None,
+                _ => bail!("invalid note {:?}", note),
+            };
+            let sharp = chars.next_if(|c| *c == '#').is_some();
+            // the dot is found before the octave as often as after it
+            let mut dotted = chars.next_if_eq(&'.').is_some();
+            let octave = digits(&mut chars, note)?.unwrap_or(octave);
+            dotted |= chars.next_if_eq(&'.').is_some();
+            if chars.next().is_some() || !(1..=8).contains(&octave) {
+                bail!("invalid note {:?}", note);
+            }
+            let mut millis = whole / length;
+            if dotted {
+                millis += millis / 2;
+            }
+            Ok(Note {
+                frequency: semitone.map(|semitone| frequency(semitone + sharp as u32, octave)),
+                duration: Duration::from_millis(millis as u64),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(frequency: Option<u32>, millis: u64) -> Note {
+        Note { frequency, duration: Duration::from_millis(millis) }
+    }
+
+    #[test]
+    fn notes_and_defaults() {
+        // a whole note is 2 s at 120 bpm
+        let notes = parse("test:d=4,o=5,b=120:8c6,p,4a,g#,2e.,e.6,32b").unwrap();
+        assert_eq!(notes, vec![
+            note(Some(1047), 250),
+            note(None, 500),
+            note(Some(880), 500),
+            note(Some(831), 500),
+            note(Some(659), 1500),
+            note(Some(1319), 750),
+            note(Some(988), 62),
+        ]);
+    }
+
+    #[test]
+    fn spec_defaults() {
+        // d=4, o=6, b=63 when the section is empty
+        assert_eq!(parse("x::a").unwrap(), vec![note(Some(1760), 952)]);
+        assert_eq!(parse("x: D = 8 :H5").unwrap(), vec![note(Some(988), 476)]);
+    }
+
+    #[test]
+    fn rejected() {
+        for text in ["no sections", "x:b=0:c", "x:q=1:c", "x:d=4:3c", "x:d=4:x", "x:d=4:c9", "x:d=4:c5x",
+                     "x:d=4:99999999999c", "x:d=4:c99999999999"] {
+            assert!(parse(text).is_err(), "{}", text);
+        }
+        assert!(parse("x:d=4:").unwrap().is_empty());
+        assert!(parse("x:d=4:99999999999c").unwrap_err().to_string().contains("too large"));
+    }
+}
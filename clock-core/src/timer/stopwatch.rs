@@ -0,0 +1,109 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// laps kept, the oldest go first
+const MAX_LAPS: usize = 20;
+
+/// runs on the monotonic clock so a time sync in the middle does not bend it
+#[derive(Default)]
+pub struct Stopwatch {
+    /// while running, when it was last started
+    started: Option<Instant>,
+    /// time run before `started`
+    before: Duration,
+    /// total time at each lap, newest first
+    laps: VecDeque<Duration>,
+    /// laps taken, the kept ones included
+    taken: usize,
+    /// total time at the newest lap that no longer fits, the start of the oldest kept one
+    dropped: Duration,
+}
+
+impl Stopwatch {
+    pub fn running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.before + self.started.map_or(Duration::ZERO, |started| now.saturating_duration_since(started))
+    }
+
+    pub fn start_stop(&mut self, now: Instant) {
+        match self.started.take() {
+            Some(started) => self.before += now.saturating_duration_since(started),
+            None => self.started = Some(now),
+        }
+    }
+
+    /// a lap while running, back to zero while stopped
+    pub fn lap_reset(&mut self, now: Instant) {
+        match self.running() {
+            true => {
+                self.laps.push_front(self.elapsed(now));
+                self.taken += 1;
+                if self.laps.len() > MAX_LAPS {
+                    self.dropped = self.laps.pop_back().unwrap_or_default();
+                }
+            }
+            false => *self = Self::default(),
+        }
+    }
+
+    /// number, length and total time of each lap, newest first
+    pub fn laps(&self) -> impl Iterator<Item = (usize, Duration, Duration)> + '_ {
+        self.laps.iter().enumerate().map(move |(index, total)| {
+            let previous = self.laps.get(index + 1).copied().unwrap_or(self.dropped);
+            (self.taken - index, *total - previous, *total)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn laps_newest_first() {
+        let start = Instant::now();
+        let mut stopwatch = Stopwatch::default();
+        stopwatch.start_stop(start);
+        stopwatch.lap_reset(start + Duration::from_secs(3));
+        stopwatch.lap_reset(start + Duration::from_secs(8));
+        let laps: Vec<_> = stopwatch.laps().collect();
+        assert_eq!(laps, vec![
+            (2, Duration::from_secs(5), Duration::from_secs(8)),
+            (1, Duration::from_secs(3), Duration::from_secs(3)),
+        ]);
+    }
+
+    #[test]
+    fn laps_past_the_limit_keep_numbers_and_lengths() {
+        let start = Instant::now();
+        let mut stopwatch = Stopwatch::default();
+        stopwatch.start_stop(start);
+        // lap n ends at n * 10 seconds
+        for lap in 1..=MAX_LAPS as u64 + 3 {
+            stopwatch.lap_reset(start + Duration::from_secs(lap * 10));
+        }
+        let laps: Vec<_> = stopwatch.laps().collect();
+        assert_eq!(laps.len(), MAX_LAPS);
+        assert_eq!(laps[0], (23, Duration::from_secs(10), Duration::from_secs(230)));
+        assert_eq!(laps[MAX_LAPS - 1], (4, Duration::from_secs(10), Duration::from_secs(40)));
+    }
+
+    #[test]
+    fn reset_while_stopped() {
+        let start = Instant::now();
+        let mut stopwatch = Stopwatch::default();
+        stopwatch.start_stop(start);
+        stopwatch.lap_reset(start + Duration::from_secs(1));
+        stopwatch.start_stop(start + Duration::from_secs(2));
+        assert_eq!(stopwatch.elapsed(start + Duration::from_secs(9)), Duration::from_secs(2));
+        stopwatch.lap_reset(start + Duration::from_secs(9));
+        assert_eq!(stopwatch.laps().count(), 0);
+        assert_eq!(stopwatch.elapsed(start + Duration::from_secs(9)), Duration::ZERO);
+        stopwatch.start_stop(start + Duration::from_secs(10));
+        stopwatch.lap_reset(start + Duration::from_secs(11));
+        assert_eq!(stopwatch.laps().next(), Some((1, Duration::from_secs(1), Duration::from_secs(1))));
+    }
+}
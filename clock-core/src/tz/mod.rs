@@ -0,0 +1,104 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, Utc};
+
+use posix::PosixTz;
+
+pub mod posix;
+pub mod zones;
+
+/// a time zone given as IANA name or POSIX TZ string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Zone {
+    rule: PosixTz,
+}
+
+impl Zone {
+    /// `Europe/Berlin` from the embedded table, anything else is parsed as POSIX TZ
+    pub fn parse(value: &str) -> Option<Self> {
+        let rule = zones::lookup(value).unwrap_or(value);
+        Some(Self { rule: PosixTz::parse(rule)? })
+    }
+    pub fn fixed(offset: i32) -> Self {
+        Self { rule: PosixTz::fixed(offset) }
+    }
+    pub fn offset_at(&self, utc: &DateTime<Utc>) -> FixedOffset {
+        FixedOffset::east_opt(self.rule.offset_at(utc.timestamp()))
+            .unwrap_or(FixedOffset::east_opt(0).unwrap())
+    }
+    pub fn abbreviation(&self, utc: &DateTime<Utc>) -> &str {
+        self.rule.name_at(utc.timestamp())
+    }
+    pub fn to_local(&self, utc: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        utc.with_timezone(&self.offset_at(utc))
+    }
+    /// earliest instant that reads `local` on the wall clock, a time skipped by a forward jump
+    /// gives the instant just after it, later on the wall clock by the size of the jump
+    pub fn from_local(&self, local: &NaiveDateTime) -> DateTime<Utc> {
+        let at = |offset: FixedOffset| (*local - Duration::seconds(offset.local_minus_utc() as i64)).and_utc();
+        // transitions are months apart, a day either side has the offsets on both sides of one
+        let before = self.offset_at(&(local.and_utc() - Duration::days(1)));
+        let after = self.offset_at(&(local.and_utc() + Duration::days(1)));
+        [before, after]
+            .into_iter()
+            .map(|offset| (offset, at(offset)))
+            .filter(|(offset, utc)| self.offset_at(utc) == *offset)
+            .map(|(_, utc)| utc)
+            .min()
+            .unwrap_or(at(before))
+    }
+}
+
+impl Default for Zone {
+    fn default() -> Self {
+        Self::fixed(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn local(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, 0).unwrap()
+    }
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        local(y, mo, d, h, mi).and_utc()
+    }
+
+    #[test]
+    fn iana_names_and_posix_strings() {
+        let berlin = Zone::parse("Europe/Berlin").unwrap();
+        assert_eq!(berlin, Zone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap());
+        assert_eq!(berlin.abbreviation(&utc(2024, 7, 1, 0, 0)), "CEST");
+        assert_eq!(berlin.to_local(&utc(2024, 1, 1, 12, 0)).naive_local(), local(2024, 1, 1, 13, 0));
+        assert!(Zone::parse("Nowhere/Special").is_none());
+    }
+
+    #[test]
+    fn from_local_in_forward_gap() {
+        // 02:00 to 03:00 on 2024-03-31 does not exist in Berlin
+        let berlin = Zone::parse("Europe/Berlin").unwrap();
+        assert_eq!(berlin.from_local(&local(2024, 3, 31, 1, 59)), utc(2024, 3, 31, 0, 59));
+        let skipped = berlin.from_local(&local(2024, 3, 31, 2, 30));
+        assert_eq!(skipped, utc(2024, 3, 31, 1, 30));
+        assert_eq!(berlin.to_local(&skipped).naive_local(), local(2024, 3, 31, 3, 30));
+        assert_eq!(berlin.from_local(&local(2024, 3, 31, 3, 0)), utc(2024, 3, 31, 1, 0));
+    }
+
+    #[test]
+    fn from_local_in_repeated_hour() {
+        // 02:00 to 03:00 on 2024-10-27 comes twice in Berlin, the summer time one is first
+        let berlin = Zone::parse("Europe/Berlin").unwrap();
+        assert_eq!(berlin.from_local(&local(2024, 10, 27, 2, 30)), utc(2024, 10, 27, 0, 30));
+        assert_eq!(berlin.from_local(&local(2024, 10, 27, 3, 0)), utc(2024, 10, 27, 2, 0));
+    }
+
+    #[test]
+    fn from_local_in_southern_gap() {
+        // 02:00 to 03:00 on 2024-10-06 does not exist in Sydney
+        let sydney = Zone::parse("Australia/Sydney").unwrap();
+        assert_eq!(sydney.from_local(&local(2024, 10, 6, 2, 30)), utc(2024, 10, 5, 16, 30));
+        assert_eq!(sydney.from_local(&local(2024, 4, 7, 2, 30)), utc(2024, 4, 6, 15, 30));
+    }
+}
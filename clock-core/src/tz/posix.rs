@@ -0,0 +1,280 @@
+use chrono::{Datelike, DateTime, Duration, NaiveDate};
+
+/// rules without an explicit time switch at 02:00 local
+const DEFAULT_RULE_TIME: i32 = 2 * 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleDate {
+    /// `Jn`, 1 to 365, February 29 is never counted
+    Julian1(u16),
+    /// `n`, 0 to 365, February 29 is counted in leap years
+    Julian0(u16),
+    /// `Mm.w.d`, day `d` (0 is Sunday) of week `w` (5 is the last) of month `m`
+    MonthWeekDay(u32, u32, u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rule {
+    date: RuleDate,
+    /// seconds after local midnight, may be negative or past 24h
+    time: i32,
+}
+
+impl Rule {
+    fn date(&self, year: i32) -> Option<NaiveDate> {
+        match self.date {
+            RuleDate::Julian1(day) => {
+                let leap = NaiveDate::from_ymd_opt(year, 2, 29).is_some();
+                let ordinal = if leap && day >= 60 { day + 1 } else { day };
+                NaiveDate::from_yo_opt(year, ordinal as u32)
+            }
+            RuleDate::Julian0(day) => NaiveDate::from_yo_opt(year, day as u32 + 1),
+            RuleDate::MonthWeekDay(month, week, weekday) => {
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let shift = (weekday + 7 - first.weekday().num_days_from_sunday()) % 7;
+                let mut date = first + Duration::days((shift + (week - 1) * 7) as i64);
+                while date.month() != month {
+                    date -= Duration::days(7);
+                }
+                Some(date)
+            }
+        }
+    }
+    /// utc seconds of the switch, `offset` is the one in effect before it
+    fn transition(&self, year: i32, offset: i32) -> Option<i64> {
+        let midnight = self.date(year)?.and_hms_opt(0, 0, 0)?.and_utc().timestamp();
+        Some(midnight + self.time as i64 - offset as i64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dst {
+    name: String,
+    offset: i32,
+    start: Rule,
+    end: Rule,
+}
+
+/// a POSIX TZ string such as `CET-1CEST,M3.5.0,M10.5.0/3`, offsets are kept
+/// in seconds east of utc, the opposite sign of the string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PosixTz {
+    name: String,
+    offset: i32,
+    dst: Option<Dst>,
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.i).copied()
+    }
+    fn eat(&mut self, c: u8) -> bool {
+        match self.peek() == Some(c) {
+            true => {
+                self.i += 1;
+                true
+            }
+            false => false,
+        }
+    }
+    fn number(&mut self) -> Option<i32> {
+        let start = self.i;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.i += 1;
+        }
+        std::str::from_utf8(&self.s[start..self.i]).ok()?.parse().ok()
+    }
+    /// `abc` or `<+0330>`
+    fn name(&mut self) -> Option<String> {
+        let start = self.i;
+        let name = match self.eat(b'<') {
+            true => {
+                while self.peek().is_some_and(|c| c != b'>') {
+                    self.i += 1;
+                }
+                let name = &self.s[start + 1..self.i];
+                if !self.eat(b'>') {
+                    return None;
+                }
+                name
+            }
+            false => {
+                while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    self.i += 1;
+                }
+                &self.s[start..self.i]
+            }
+        };
+        match name.len() >= 3 {
+            true => Some(String::from_utf8_lossy(name).into()),
+            false => None,
+        }
+    }
+    /// `[+-]hh[:mm[:ss]]` in seconds
+    fn time(&mut self) -> Option<i32> {
+        let sign = match self.peek() {
+            Some(b'-') => { self.i += 1; -1 }
+            Some(b'+') => { self.i += 1; 1 }
+            _ => 1,
+        };
+        let mut seconds = self.number()? * 3600;
+        if self.eat(b':') {
+            seconds += self.number()? * 60;
+            if self.eat(b':') {
+                seconds += self.number()?;
+            }
+        }
+        Some(sign * seconds)
+    }
+    fn rule(&mut self) -> Option<Rule> {
+        let date = match self.peek()? {
+            b'J' => {
+                self.i += 1;
+                RuleDate::Julian1(self.number().filter(|d| (1..=365).contains(d))? as u16)
+            }
+            b'M' => {
+                self.i += 1;
+                let month = self.number().filter(|m| (1..=12).contains(m))?;
+                self.eat(b'.').then_some(())?;
+                let week = self.number().filter(|w| (1..=5).contains(w))?;
+                self.eat(b'.').then_some(())?;
+                let day = self.number().filter(|d| (0..=6).contains(d))?;
+                RuleDate::MonthWeekDay(month as u32, week as u32, day as u32)
+            }
+            _ => RuleDate::Julian0(self.number().filter(|d| (0..=365).contains(d))? as u16),
+        };
+        let time = match self.eat(b'/') {
+            true => self.time()?,
+            false => DEFAULT_RULE_TIME,
+        };
+        Some(Rule { date, time })
+    }
+}
+
+impl PosixTz {
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parser = Parser { s: value.trim().as_bytes(), i: 0 };
+        let name = parser.name()?;
+        let offset = -parser.time()?;
+        if parser.peek().is_none() {
+            return Some(Self { name, offset, dst: None });
+        }
+        let dst_name = parser.name()?;
+        let dst_offset = match parser.peek() {
+            Some(b',') | None => offset + 3600,
+            _ => -parser.time()?,
+        };
+        // no rule, the US one is what POSIX implementations assume
+        let (start, end) = match parser.eat(b',') {
+            true => {
+                let start = parser.rule()?;
+                parser.eat(b',').then_some(())?;
+                (start, parser.rule()?)
+            }
+            false => (
+                Rule { date: RuleDate::MonthWeekDay(3, 2, 0), time: DEFAULT_RULE_TIME },
+                Rule { date: RuleDate::MonthWeekDay(11, 1, 0), time: DEFAULT_RULE_TIME },
+            ),
+        };
+        if parser.peek().is_some() {
+            return None;
+        }
+        Some(Self { name, offset, dst: Some(Dst { name: dst_name, offset: dst_offset, start, end }) })
+    }
+
+    /// a zone without daylight saving, `offset` seconds east of utc
+    pub fn fixed(offset: i32) -> Self {
+        let (sign, abs) = if offset < 0 { ('-', -offset) } else { ('+', offset) };
+        let name = match abs % 3600 {
+            0 => format!("{}{:02}", sign, abs / 3600),
+            _ => format!("{}{:02}{:02}", sign, abs / 3600, abs % 3600 / 60),
+        };
+        Self { name, offset, dst: None }
+    }
+
+    fn is_dst(&self, utc: i64) -> Option<&Dst> {
+        let dst = self.dst.as_ref()?;
+        let year = DateTime::from_timestamp(utc + self.offset as i64, 0)?.year();
+        let start = dst.start.transition(year, self.offset)?;
+        let end = dst.end.transition(year, dst.offset)?;
+        let in_dst = match start < end {
+            true => utc >= start && utc < end,
+            // southern hemisphere, daylight saving spans the new year
+            false => utc < end || utc >= start,
+        };
+        in_dst.then_some(dst)
+    }
+
+    /// seconds east of utc in effect at `utc` unix seconds
+    pub fn offset_at(&self, utc: i64) -> i32 {
+        self.is_dst(utc).map_or(self.offset, |dst| dst.offset)
+    }
+
+    /// abbreviation in effect at `utc` unix seconds, `CET` or `CEST`
+    pub fn name_at(&self, utc: i64) -> &str {
+        self.is_dst(utc).map_or(self.name.as_str(), |dst| dst.name.as_str())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (unix second the offset changes, offset before, offset after, abbreviation after)
+    fn check(tz: &PosixTz, transitions: &[(i64, i32, i32, &str)]) {
+        for &(at, before, after, name) in transitions {
+            assert_eq!(tz.offset_at(at - 1), before, "one second before {}", at);
+            assert_eq!(tz.offset_at(at), after, "at {}", at);
+            assert_eq!(tz.name_at(at), name);
+        }
+    }
+
+    #[test]
+    fn central_europe() {
+        let tz = PosixTz::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        check(&tz, &[
+            // 2024-03-31 01:00:00 UTC, 02:00 CET becomes 03:00 CEST
+            (1_711_846_800, 3600, 7200, "CEST"),
+            // 2024-10-27 01:00:00 UTC, 03:00 CEST becomes 02:00 CET
+            (1_729_990_800, 7200, 3600, "CET"),
+            // 2025-03-30 01:00:00 UTC and 2025-10-26 01:00:00 UTC
+            (1_743_296_400, 3600, 7200, "CEST"),
+            (1_761_440_400, 7200, 3600, "CET"),
+        ]);
+    }
+
+    #[test]
+    fn southern_hemisphere() {
+        let tz = PosixTz::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        check(&tz, &[
+            // 2024-04-06 16:00:00 UTC, 03:00 AEDT becomes 02:00 AEST
+            (1_712_419_200, 39600, 36000, "AEST"),
+            // 2024-10-05 16:00:00 UTC, 02:00 AEST becomes 03:00 AEDT
+            (1_728_144_000, 36000, 39600, "AEDT"),
+        ]);
+        // summer time across the new year
+        assert_eq!(tz.name_at(1_704_067_200), "AEDT");
+        assert_eq!(tz.name_at(1_719_792_000), "AEST");
+    }
+
+    #[test]
+    fn rules_and_offsets() {
+        // last Sunday of March, second Sunday of March, the 60th day never being February 29
+        let last = Rule { date: RuleDate::MonthWeekDay(3, 5, 0), time: DEFAULT_RULE_TIME };
+        assert_eq!(last.date(2024), NaiveDate::from_ymd_opt(2024, 3, 31));
+        let second = Rule { date: RuleDate::MonthWeekDay(3, 2, 0), time: DEFAULT_RULE_TIME };
+        assert_eq!(second.date(2024), NaiveDate::from_ymd_opt(2024, 3, 10));
+        assert_eq!(Rule { date: RuleDate::Julian1(60), time: 0 }.date(2024), NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(Rule { date: RuleDate::Julian0(59), time: 0 }.date(2024), NaiveDate::from_ymd_opt(2024, 2, 29));
+        assert_eq!(PosixTz::parse("NST3:30NDT,M3.2.0,M11.1.0").unwrap().offset_at(0), -(3 * 3600 + 1800));
+        assert_eq!(PosixTz::parse("<-03>3").unwrap().name_at(0), "-03");
+        assert_eq!(PosixTz::fixed(19800).name_at(0), "+0530");
+        assert!(PosixTz::parse("CET-1CEST,M3.5.0").is_none());
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, FixedOffset, Timelike};
+
+use crate::display::clock::DateCache;
+use crate::fs::config::{Chime, ChimeSound, CONFIG};
+
+const BEEP: &str = "beep:d=8,o=7,b=120:c";
+/// a low note and a rest for each strike
+const STRIKE: &str = "4a4,4p";
+
+/// decides when the clock chimes, the time comes from the caller like for the alarms
+pub struct Chimes {
+    chime: Chime,
+    /// time of the last poll, a chime goes with the first poll in a new minute
+    last: Option<DateCache>,
+}
+
+impl Chimes {
+    pub fn new(chime: Chime) -> Self {
+        Self { chime, last: None }
+    }
+
+    pub fn load() -> Self {
+        Self::new(CONFIG.as_ref().map_or(Chime::default(), |config| config.chime.clone()))
+    }
+
+    fn quiet(&self, hour: u32) -> bool {
+        let (from, until) = (self.chime.quiet_from, self.chime.quiet_until);
+        match from <= until {
+            true => (from..until).contains(&hour),
+            // the window runs over midnight
+            false => hour >= from || hour < until,
+        }
+    }
+
+    /// the ringtone to play when `now` begins an hour or a half hour, nothing on the first
+    /// poll after a start so a restart on the hour does not chime
+    pub fn poll(&mut self, now: &DateTime<FixedOffset>) -> Option<String> {
+        let changed = self.last.map(|last| last.minute_changed(now));
+        self.last.get_or_insert_with(DateCache::default).update(now);
+        if changed != Some(true) || self.chime.sound == ChimeSound::Off || self.quiet(now.hour()) {
+            return None;
+        }
+        let half = match now.minute() {
+            0 => false,
+            30 if self.chime.half_hour => true,
+            _ => return None,
+        };
+        let strikes = match (self.chime.sound, half) {
+            (ChimeSound::Strikes, false) => (now.hour() + 11) % 12 + 1,
+            (ChimeSound::Strikes, true) => 1,
+            (ChimeSound::Melody, false) => return Some(self.chime.melody.clone()),
+            _ => return Some(BEEP.into()),
+        };
+        Some(format!("strikes:d=4,o=5,b=90:{}", vec![STRIKE; strikes as usize].join(",")))
+    }
+}
@@ -0,0 +1,21 @@
+use log::error;
+
+use crate::fs::config::CONFIG;
+use crate::fs::store;
+
+pub mod chime;
+
+pub use clock_core::alarm::{Alarm, Scheduler};
+
+const ALARMS_KEY: &str = "alarms";
+
+/// the alarms kept in flash, the ones from the config on the first start
+pub fn load() -> Scheduler {
+    let alarms = store::load(ALARMS_KEY)
+        .unwrap_or_else(|| CONFIG.as_ref().map_or(Vec::new(), |config| config.alarms.clone()));
+    Scheduler::new(alarms, Box::new(|alarms: &[Alarm]| {
+        if let Err(e) = store::save(ALARMS_KEY, &alarms) {
+            error!("save alarms failed: {}", e);
+        }
+    }))
+}
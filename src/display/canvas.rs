@@ -0,0 +1,155 @@
+use std::convert::Infallible;
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::pixelcolor::Bgr565;
+use embedded_graphics::prelude::{PointsIter, RgbColor};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+/// mixes `color` over `base`, `coverage` 0 keeps `base` and 1 gives `color`
+pub fn blend(base: Bgr565, color: Bgr565, coverage: f32) -> Bgr565 {
+    let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * coverage + 0.5) as u8;
+    Bgr565::new(mix(base.r(), color.r()), mix(base.g(), color.g()), mix(base.b(), color.b()))
+}
+
+/// off-screen copy of part of the display, layers are drawn into it back to front and
+/// the result goes out in one transfer, so nothing on screen is ever erased by overdrawing
+///
+/// coordinates are the display's, the shapes take the pixel (x, y) as the square from x to x + 1
+pub struct Canvas {
+    area: Rectangle,
+    pixels: Vec<Bgr565>,
+}
+
+impl Canvas {
+    pub fn new(area: Rectangle, color: Bgr565) -> Self {
+        Self { area, pixels: vec![color; (area.size.width * area.size.height) as usize] }
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        match self.area.contains(point) {
+            true => {
+                let offset = point - self.area.top_left;
+                Some(offset.y as usize * self.area.size.width as usize + offset.x as usize)
+            }
+            false => None,
+        }
+    }
+
+    pub fn blend(&mut self, point: Point, color: Bgr565, coverage: f32) {
+        if coverage <= 0.0 {
+            return;
+        }
+        if let Some(index) = self.index(point) {
+            self.pixels[index] = match coverage >= 1.0 {
+                true => color,
+                false => blend(self.pixels[index], color, coverage),
+            };
+        }
+    }
+
+    /// pixels of the canvas inside `[left, right] x [top, bottom]`, a pixel margin around it for the edge
+    fn span(&self, left: f32, top: f32, right: f32, bottom: f32) -> Rectangle {
+        let top_left = Point::new(left.floor() as i32 - 1, top.floor() as i32 - 1);
+        let bottom_right = Point::new(right.ceil() as i32 + 1, bottom.ceil() as i32 + 1);
+        let size = bottom_right - top_left;
+        self.area.intersection(&Rectangle::new(top_left, Size::new(size.x as u32, size.y as u32)))
+    }
+
+    /// every pixel of the canvas near a shape with its signed distance from the shape's edge,
+    /// negative inside, turned into an anti-aliased fill
+    fn fill_distance(&mut self, span: Rectangle, color: Bgr565, distance: impl Fn(f32, f32) -> f32) {
+        for point in span.points() {
+            let coverage = 0.5 - distance(point.x as f32 + 0.5, point.y as f32 + 0.5);
+            self.blend(point, color, coverage.min(1.0));
+        }
+    }
+
+    /// convex polygon, the corners in either winding order
+    pub fn fill_convex(&mut self, points: &[(f32, f32)], color: Bgr565) {
+        let area2 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|((x0, y0), (x1, y1))| x0 * y1 - x1 * y0)
+            .sum::<f32>();
+        let winding = if area2 < 0.0 { -1.0 } else { 1.0 };
+        // each edge as its outward unit normal and offset, degenerate edges are dropped
+        let edges = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .filter_map(|((x0, y0), (x1, y1))| {
+                let (dx, dy) = (x1 - x0, y1 - y0);
+                let length = (dx * dx + dy * dy).sqrt();
+                match length > 1e-3 {
+                    true => {
+                        let (nx, ny) = (winding * dy / length, -winding * dx / length);
+                        Some((nx, ny, nx * x0 + ny * y0))
+                    }
+                    false => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        if edges.len() < 3 {
+            return;
+        }
+        let (left, top, right, bottom) = points.iter().fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(left, top, right, bottom), (x, y)| (left.min(*x), top.min(*y), right.max(*x), bottom.max(*y)),
+        );
+        let span = self.span(left, top, right, bottom);
+        self.fill_distance(span, color, |x, y| {
+            edges.iter().fold(f32::MIN, |distance, (nx, ny, offset)| distance.max(nx * x + ny * y - offset))
+        });
+    }
+
+    pub fn fill_disc(&mut self, center: (f32, f32), radius: f32, color: Bgr565) {
+        let (cx, cy) = center;
+        let span = self.span(cx - radius, cy - radius, cx + radius, cy + radius);
+        self.fill_distance(span, color, |x, y| ((x - cx) * (x - cx) + (y - cy) * (y - cy)).sqrt() - radius);
+    }
+
+    /// circle outline `width` thick inside `radius`
+    pub fn stroke_circle(&mut self, center: (f32, f32), radius: f32, width: f32, color: Bgr565) {
+        let (cx, cy) = center;
+        let middle = radius - width / 2.0;
+        // most of the square around a ring is nowhere near it, skip those without a square root
+        let (inner, outer) = ((middle - width / 2.0 - 1.0).max(0.0), middle + width / 2.0 + 1.0);
+        let (inner2, outer2) = (inner * inner, outer * outer);
+        let span = self.span(cx - radius, cy - radius, cx + radius, cy + radius);
+        self.fill_distance(span, color, |x, y| {
+            let distance2 = (x - cx) * (x - cx) + (y - cy) * (y - cy);
+            match distance2 < inner2 || distance2 > outer2 {
+                true => f32::MAX,
+                false => (distance2.sqrt() - middle).abs() - width / 2.0,
+            }
+        });
+    }
+
+    pub fn flush<D: DrawTarget<Color = Bgr565>>(&self, target: &mut D) -> Result<(), D::Error> {
+        target.fill_contiguous(&self.area, self.pixels.iter().copied())
+    }
+}
+
+impl Dimensions for Canvas {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Bgr565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some(index) = self.index(point) {
+                self.pixels[index] = color;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,25 +1,37 @@
 #![allow(dead_code)]
 
+use std::convert::Infallible;
 use std::f32::consts::PI;
 use std::marker::PhantomData;
-use std::ops::Deref;
 
-use chrono::{Datelike, DateTime, FixedOffset, Local, Timelike};
+use chrono::{Datelike, DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::Drawable;
-use embedded_graphics::geometry::{Dimensions, Point};
+use embedded_graphics::geometry::{Dimensions, Point, Size};
 use embedded_graphics::mono_font::ascii::{FONT_5X7, FONT_8X13};
 use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::pixelcolor::Bgr565;
-use embedded_graphics::prelude::{Primitive, RgbColor, Transform};
-use embedded_graphics::primitives::{
-    Circle, Line, PrimitiveStyle, Rectangle,
-};
+use embedded_graphics::prelude::RgbColor;
+use embedded_graphics::primitives::{Circle, Rectangle};
 use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+use log::error;
+use u8g2_fonts::{Error as FontError, FontRenderer, fonts};
+use u8g2_fonts::types::{FontColor, HorizontalAlignment, VerticalPosition};
 
-use crate::fs::config::CONFIG;
+use crate::calendar::lunar_line;
+use crate::display::canvas::Canvas;
+use crate::display::compositor::{Compositor, union};
+use crate::display::face::{formats, WatchFace};
+use crate::fs::config::{CONFIG, HourFormat};
+
+/// seconds the date and the lunar line each stay on screen
+const DATE_LINE_PERIOD: u32 = 5;
+/// 12px WenQuanYi covering GB2312, the ascii mono fonts have no CJK glyphs
+const CJK_FONT: FontRenderer = FontRenderer::new::<fonts::u8g2_font_wqy12_t_gb2312>();
+const CAP_RADIUS: f32 = 3.0;
 
 #[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Hand {
     Second,
     Minute,
@@ -34,15 +46,49 @@ impl Hand {
             Hand::Hour => 0,
         }
     }
+    fn color(&self) -> Bgr565 {
+        match self {
+            Hand::Second => Bgr565::BLUE,
+            Hand::Minute => Bgr565::GREEN,
+            Hand::Hour => Bgr565::RED,
+        }
+    }
+    /// how far short of the rim the tip stops
+    fn length_delta(&self) -> f32 {
+        match self {
+            Hand::Second => -10.0,
+            Hand::Minute => -15.0,
+            Hand::Hour => -20.0,
+        }
+    }
+    /// convex pieces of the hand pointing at 12, each as (distance from the center, half width)
+    /// pairs from the tail to the tip that get mirrored on both sides
+    fn profile(&self, length: f32) -> Vec<Vec<(f32, f32)>> {
+        match self {
+            // a thin shaft balanced by a wider tail
+            Hand::Second => vec![vec![(-14.0, 0.6), (length, 0.5)], vec![(-14.0, 1.8), (-5.0, 1.4)]],
+            Hand::Minute => vec![vec![(-4.0, 2.0), (length - 4.0, 1.3), (length, 0.0)]],
+            Hand::Hour => vec![vec![(-4.0, 2.6), (length - 5.0, 1.9), (length, 0.0)]],
+        }
+    }
 }
+
+/// point `radius` from `center` towards `angle`, clockwise from 12
 #[inline(always)]
-pub fn polar(circle: &Circle, angle: f32, radius_delta: i32) -> Point {
-    let radius = circle.diameter as f32 / 2.0 + radius_delta as f32;
-    circle.center()
-        + Point::new(
-            (angle.sin() * radius) as i32,
-            -(angle.cos() * radius) as i32,
-        )
+fn polar(center: (f32, f32), angle: f32, radius: f32) -> (f32, f32) {
+    (center.0 + angle.sin() * radius, center.1 - angle.cos() * radius)
+}
+
+/// pixels touched by a polygon, with a pixel of margin for its anti-aliased edge
+fn bounds(points: &[(f32, f32)]) -> Rectangle {
+    let (left, top, right, bottom) = points.iter().fold(
+        (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+        |(left, top, right, bottom), (x, y)| (left.min(*x), top.min(*y), right.max(*x), bottom.max(*y)),
+    );
+    Rectangle::with_corners(
+        Point::new(left.floor() as i32 - 1, top.floor() as i32 - 1),
+        Point::new(right.ceil() as i32 + 1, bottom.ceil() as i32 + 1),
+    )
 }
 
 macro_rules! hour_to_angle {
@@ -64,85 +110,130 @@ pub struct DateCache{
     pub day: u32,
     pub hour: u32,
     pub minute: u32,
-    pub second: u32
+    pub second: u32,
+    pub millisecond: u32,
 }
 impl Default for DateCache {
     fn default() -> Self {
-        Self { year: 0, month: 0, day: 0, hour: 25, minute: 60, second: 60 }
+        Self { year: 0, month: 0, day: 0, hour: 25, minute: 60, second: 60, millisecond: 0 }
     }
 }
 impl From<DateTime<FixedOffset>> for DateCache {
     fn from(value: DateTime<FixedOffset>) -> Self {
-        DateCache { year: value.year() as u32, month: value.month(), day: value.day(), hour: value.hour(), minute: value.minute(), second: value.second() }
+        // chrono keeps a leap second in the nanoseconds, second 59 + 1s reads as 60
+        DateCache { year: value.year() as u32, month: value.month(), day: value.day(), hour: value.hour(), minute: value.minute(), second: value.second() + value.nanosecond() / 1_000_000_000, millisecond: value.nanosecond() % 1_000_000_000 / 1_000_000 }
     }
 }
 impl DateCache {
+    /// the cached time, `None` while nothing is cached
+    pub fn naive(&self) -> Option<NaiveDateTime> {
+        let date = NaiveDate::from_ymd_opt(self.year as i32, self.month, self.day)?;
+        let time = match self.second {
+            60 => NaiveTime::from_hms_milli_opt(self.hour, self.minute, 59, 1000 + self.millisecond)?,
+            second => NaiveTime::from_hms_milli_opt(self.hour, self.minute, second, self.millisecond)?,
+        };
+        Some(date.and_time(time))
+    }
+    /// `value` is in another minute than the cached time, or nothing is cached yet
+    pub fn minute_changed(&self, value: &DateTime<FixedOffset>) -> bool {
+        self.minute != value.minute() || self.hour != value.hour() || self.day != value.day()
+            || self.month != value.month() || self.year != value.year() as u32
+    }
     pub fn update(&mut self, value: &DateTime<FixedOffset> ){
         self.year = value.year() as u32; 
         self.month = value.month(); 
         self.day = value.day(); 
         self.hour = value.hour(); 
         self.minute = value.minute(); 
-        self.second = value.second()
+        self.second = value.second() + value.nanosecond() / 1_000_000_000;
+        self.millisecond = value.nanosecond() % 1_000_000_000 / 1_000_000;
     }
 }
 
+/// something drawn over the dial, a frame of the face is a list of them with the areas they cover
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Hand(Hand, f32),
+    Text(String),
+    /// centered in the CJK font
+    Cjk(String),
+}
+
 pub struct Clock<'a, D: DrawTarget<Color = Bgr565>> {
     width: u32,
     height: u32,
     face: Circle,
-    text: DateCache,
     size: u32,
     bg_color: Bgr565,
     fg_color: Bgr565,
-    date_fixed_offset: i32,
     text_font: MonoTextStyle<'a, Bgr565>,
-    text_base_position: u32,
+    hour_format: HourFormat,
+    date_format: String,
+    lunar_calendar: bool,
+    /// second hand moves with the milliseconds
+    sweep: bool,
+    /// lunar line of the day, the solar terms are too slow to work out every time
+    lunar: Option<(NaiveDate, Option<String>)>,
+    /// the frame on screen, `None` until the face is drawn
+    shown: Option<Vec<(Element, Rectangle)>>,
     _d: PhantomData<D>,
 
 }
-impl<D: DrawTarget<Color = Bgr565>> Clock<'_, D>
+impl<'a, D: DrawTarget<Color = Bgr565>> Clock<'a, D>
 {
     pub fn new(width: u32, height: u32, size: u32, bg: Bgr565, fg: Bgr565) -> Self {
         let face = Circle::with_center(
             Point::new((width / 2) as i32, (height / 2) as i32),
             width.min(height) - 2 * size,
         );
-        let date_fixed_offset = match CONFIG.deref() {
-            None => 0,
-            Some(config) => config.date_fixed_offset,
-        };
+        let (hour_format, date_format) = formats();
+        let lunar_calendar = CONFIG.as_ref().is_some_and(|config| config.lunar_calendar);
+        let sweep = CONFIG.as_ref().is_some_and(|config| config.sweep_second_hand);
         let text_font = MonoTextStyle::new(&FONT_8X13, fg);
         Self {
             width,
             height,
             face,
-            text: DateCache::default(),
             size,
             bg_color: bg,
             fg_color: fg,
-            date_fixed_offset,
             text_font,
-            text_base_position: (width - text_font.font.character_size.width * 8) / 2,
+            hour_format,
+            date_format,
+            lunar_calendar,
+            sweep,
+            lunar: None,
+            shown: None,
             _d: Default::default(),
         }
     }
 
-    pub fn draw_face(&self, target: &mut D, color: Bgr565) -> anyhow::Result<(), D::Error>
-    {
-        self.face
-            .into_styled(PrimitiveStyle::with_stroke(color, 2))
-            .draw(target)?;
-        Circle::with_center(self.face.center(), 4)
-            .into_styled(PrimitiveStyle::with_fill(color))
-            .draw(target)?;
+    fn center(&self) -> (f32, f32) {
+        let radius = self.radius();
+        (self.face.top_left.x as f32 + radius, self.face.top_left.y as f32 + radius)
+    }
+    fn radius(&self) -> f32 {
+        self.face.diameter as f32 / 2.0
+    }
+
+    /// rim, minute ticks and numerals
+    fn draw_dial(&self, canvas: &mut Canvas) -> Result<(), Infallible> {
+        let center = self.center();
+        let radius = self.radius();
+        canvas.stroke_circle(center, radius, 1.5, self.fg_color);
+        for i in 0..60 {
+            let angle = min_to_angle!(i);
+            let (length, width) = match i % 5 {
+                0 => (5.0, 2.0),
+                _ => (2.0, 1.0),
+            };
+            let (x0, y0) = polar(center, angle, radius - 2.5);
+            let (x1, y1) = polar(center, angle, radius - 2.5 - length);
+            let (dx, dy) = (angle.cos() * width / 2.0, angle.sin() * width / 2.0);
+            canvas.fill_convex(&[(x0 - dx, y0 - dy), (x0 + dx, y0 + dy), (x1 + dx, y1 + dy), (x1 - dx, y1 - dy)], self.fg_color);
+        }
         for i in 0..12 {
-            let angle = hour_to_angle!(i);
-            let start = polar(&self.face, angle, 0);
-            let end = polar(&self.face, angle, -5);
-            Line::new(start, end)
-                .into_styled(PrimitiveStyle::with_stroke(color, 1))
-                .draw(target)?;
+            let (x, y) = polar(center, hour_to_angle!(i), radius - 13.0);
             Text::with_text_style(
                 format!(
                     "{}",
@@ -152,167 +243,205 @@ impl<D: DrawTarget<Color = Bgr565>> Clock<'_, D>
                     }
                 )
                 .as_str(),
-                polar(&self.face, angle, -8) + Point::new(0, 3),
+                Point::new(x as i32, y as i32 + 3),
                 MonoTextStyle::new(&FONT_5X7, self.fg_color),
                 TextStyleBuilder::new()
                     .alignment(Alignment::Center)
                     .baseline(Baseline::Alphabetic)
                     .build(),
             )
-            .draw(target)?;
+            .draw(canvas)?;
         }
+        Ok(())
+    }
 
+    /// outline of each convex piece of `hand` at `angle`
+    fn hand_outlines(&self, hand: &Hand, angle: f32) -> Vec<Vec<(f32, f32)>> {
+        let (cx, cy) = self.center();
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let point = |(along, across): (f32, f32)| (cx + sin * along + cos * across, cy - cos * along + sin * across);
+        hand.profile(self.radius() + hand.length_delta())
+            .into_iter()
+            .map(|profile| {
+                let left = profile.iter().map(|(along, width)| point((*along, -width)));
+                let right = profile.iter().rev().map(|(along, width)| point((*along, *width)));
+                left.chain(right).collect()
+            })
+            .collect()
+    }
+
+    fn cap_box(&self) -> Rectangle {
+        let (x, y) = self.center();
+        bounds(&[(x - CAP_RADIUS, y - CAP_RADIUS), (x + CAP_RADIUS, y + CAP_RADIUS)])
+    }
+
+    fn text<'t>(&self, value: &'t str, x: i32, y: i32) -> Text<'t, MonoTextStyle<'a, Bgr565>> {
+        Text::with_text_style(
+            value,
+            Point::new(x, y),
+            self.text_font,
+            TextStyleBuilder::new()
+                .alignment(Alignment::Left)
+                .baseline(Baseline::Top)
+                .build(),
+        )
+    }
+
+    fn lunar_line(&mut self, date: NaiveDate) -> Option<String> {
+        match self.lunar {
+            Some((day, ref line)) if day == date => line.clone(),
+            _ => {
+                let line = lunar_line(date);
+                self.lunar = Some((date, line.clone()));
+                line
+            }
+        }
+    }
+
+    /// top center of the CJK line at the bottom
+    fn cjk_position(&self) -> Point {
+        Point::new(self.width as i32 / 2, (self.height - self.text_font.font.character_size.height - 1) as i32)
+    }
+
+    fn draw_cjk(&self, canvas: &mut Canvas, value: &str) -> Result<(), Infallible> {
+        match CJK_FONT.render_aligned(value, self.cjk_position(), VerticalPosition::Top, HorizontalAlignment::Center,
+                                      FontColor::Transparent(self.fg_color), canvas) {
+            Ok(_) => {}
+            Err(FontError::DisplayError(error)) => return Err(error),
+            Err(FontError::GlyphNotFound(c)) => error!("no glyph for {}", c),
+            Err(FontError::BackgroundColorNotSupported) => {}
+        }
         Ok(())
     }
 
-    pub fn draw_hand(
-        &mut self,
-        target: &mut D,
-        value: u32,
-        length_delta: i32,
-        hand: Hand,
-        color: Bgr565,
-    ) -> anyhow::Result<(), D::Error>
-    {
-        let (stroke, old_value) = match hand {
-            Hand::Second => (1, self.text.second),
-            Hand::Minute => (2, self.text.minute),
-            Hand::Hour => (2, self.text.hour),
+    /// what the face shows at `cache` and where
+    fn layout(&mut self, cache: &DateCache) -> Vec<(Element, Rectangle)> {
+        let date = match cache.naive() {
+            Some(date) => date,
+            None => return Vec::new(),
         };
-        let angle = match hand {
-            Hand::Hour => hour_to_angle!(value),
-            _=> min_to_angle!(value)
+        let second = match self.sweep {
+            // through a leap second the hand waits on 12
+            true => (cache.second as f32 + cache.millisecond as f32 / 1000.0).min(60.0),
+            false => cache.second as f32,
         };
-        let old_angle = match hand {
-            Hand::Hour => hour_to_angle!(old_value),
-            _=> min_to_angle!(old_value)
+        let angles = [
+            hour_to_angle!(cache.hour) + min_to_angle!(cache.minute) / 12.0,
+            min_to_angle!(cache.minute),
+            min_to_angle!(second),
+        ];
+        let mut layout = [Hand::Hour, Hand::Minute, Hand::Second]
+            .into_iter()
+            .map(|hand| {
+                let angle = angles[hand.usize()];
+                let area = self.hand_outlines(&hand, angle)
+                    .iter()
+                    .fold(self.cap_box(), |area, outline| union(area, bounds(outline)));
+                (Element::Hand(hand, angle), area)
+            })
+            .collect::<Vec<_>>();
+
+        let mut segments = vec![
+            format!("{}:", date.format(self.hour_format.hour())),
+            format!("{}:", date.format("%M")),
+            // chrono prints a leap second as 60
+            format!("{}", date.format("%S")),
+        ];
+        if self.hour_format == HourFormat::H12 {
+            segments.push(format!(" {}", date.format("%p")));
+        }
+        // lay the line out from what the font renders, one element per field so only those that change are redrawn
+        let widths = segments
+            .iter()
+            .map(|value| self.text(value, 0, 0).bounding_box().size.width)
+            .collect::<Vec<u32>>();
+        let mut x = (self.width.saturating_sub(widths.iter().sum::<u32>()) / 2) as i32;
+        for (value, width) in segments.into_iter().zip(widths) {
+            let area = self.text(&value, x, 1).bounding_box();
+            layout.push((Element::Text(value), area));
+            x += width as i32;
+        }
+
+        let lunar = match self.lunar_calendar && cache.second / DATE_LINE_PERIOD % 2 == 1 {
+            true => self.lunar_line(date.date()),
+            false => None,
         };
-        match old_value == value {
-            false => {
-                let end = polar(&self.face, old_angle, length_delta);
-                Line::new(self.face.center(), end)
-                    .into_styled(PrimitiveStyle::with_stroke(self.bg_color, stroke))
-                    .draw(target)?;
-                let end = polar(&self.face, angle, length_delta);
-                Line::new(self.face.center(), end)
-                    .into_styled(PrimitiveStyle::with_stroke(color, stroke))
-                    .draw(target)?;
+        layout.push(match lunar {
+            Some(line) => {
+                let area = CJK_FONT
+                    .get_rendered_dimensions_aligned(line.as_str(), self.cjk_position(), VerticalPosition::Top, HorizontalAlignment::Center)
+                    .ok()
+                    .flatten()
+                    .unwrap_or(Rectangle::zero());
+                (Element::Cjk(line), area)
             }
-            true => {
-                let end = polar(&self.face, angle, length_delta);
-                Line::new(self.face.center(), end)
-                    .into_styled(PrimitiveStyle::with_stroke(color, stroke))
-                    .draw(target)?;
+            None => {
+                let value = format!("{}", date.format(&self.date_format));
+                let width = self.text(&value, 0, 0).bounding_box().size.width;
+                let y = (self.height - self.text_font.font.character_size.height - 1) as i32;
+                let area = self.text(&value, (self.width.saturating_sub(width) / 2) as i32, y).bounding_box();
+                (Element::Text(value), area)
             }
-        }
-        Ok(())
+        });
+        layout
     }
 
-    pub fn draw_text(
-        &mut self,
-        target: &mut D,
-        date: &DateTime<FixedOffset>,
-    ) -> anyhow::Result<(), D::Error>
-    {
-        let text_font = self.text_font;
-        let base_position =  self.text_base_position;
-        let font_width = text_font.font.character_size.width;
-        let date = date.naive_local();
-        if self.text.second != date.second() {
-            let time_str = format!("{}", date.format("%S"));
-            let mut time_text = Text::with_text_style(
-                &time_str,
-                Point::zero(),
-                text_font,
-                TextStyleBuilder::new()
-                    .alignment(Alignment::Left)
-                    .baseline(Baseline::Alphabetic)
-                    .build(),
-            );
-            time_text.translate_mut(Point::new(
-                (base_position + 6 * font_width)  as i32,
-                time_text.bounding_box().size.height as i32,
-            ));
-            let time_text_dimensions = time_text.bounding_box();
-            Rectangle::new(time_text_dimensions.top_left, time_text_dimensions.size)
-                .into_styled(PrimitiveStyle::with_fill(self.bg_color))
-                .draw(target)?;
-            time_text.draw(target)?;
-        }
-        if self.text.minute != date.minute() {
-            let time_str = format!("{}", date.format("%M:"));
-            let mut time_text = Text::with_text_style(
-                &time_str,
-                Point::zero(),
-                text_font,
-                TextStyleBuilder::new()
-                    .alignment(Alignment::Left)
-                    .baseline(Baseline::Alphabetic)
-                    .build(),
-            );
-            time_text.translate_mut(Point::new(
-                (base_position + 3 * font_width)  as i32,
-                time_text.bounding_box().size.height as i32,
-            ));
-            let time_text_dimensions = time_text.bounding_box();
-            Rectangle::new(time_text_dimensions.top_left, time_text_dimensions.size)
-                .into_styled(PrimitiveStyle::with_fill(self.bg_color))
-                .draw(target)?;
-            time_text.draw(target)?;
-        }
-        if self.text.hour != date.hour() {
-            let time_str = format!("{}", date.format("%H:"));
-            let mut time_text = Text::with_text_style(
-                &time_str,
-                Point::zero(),
-                text_font,
-                TextStyleBuilder::new()
-                    .alignment(Alignment::Left)
-                    .baseline(Baseline::Alphabetic)
-                    .build(),
-            );
-            time_text.translate_mut(Point::new(
-                (base_position)  as i32,
-                time_text.bounding_box().size.height as i32,
-            ));
-            let time_text_dimensions = time_text.bounding_box();
-            Rectangle::new(time_text_dimensions.top_left, time_text_dimensions.size)
-                .into_styled(PrimitiveStyle::with_fill(self.bg_color))
-                .draw(target)?;
-            time_text.draw(target)?;
+    /// the whole face from scratch, back to front: dial, hands, center cap and the text
+    fn compose(&self, canvas: &mut Canvas, layout: &[(Element, Rectangle)]) -> Result<(), Infallible> {
+        self.draw_dial(canvas)?;
+        for (element, _) in layout {
+            if let Element::Hand(hand, angle) = element {
+                for outline in self.hand_outlines(hand, *angle) {
+                    canvas.fill_convex(&outline, hand.color());
+                }
+            }
         }
-        let date_str = format!("{}", date.format("%Y-%m-%d"));
-        let mut date_text = Text::with_text_style(
-            &date_str,
-            Point::zero(),
-            text_font,
-            TextStyleBuilder::new()
-                .alignment(Alignment::Left)
-                .baseline(Baseline::Alphabetic)
-                .build(),
-        );
-        date_text.translate_mut(Point::new(
-            ((self.width - date_text.bounding_box().size.width) / 2) as i32,
-            (self.width - date_text.bounding_box().size.height / 2) as i32,
-        ));
-        if [self.text.year, self.text.month, self.text.day] != [date.year() as u32, date.month(), date.day()] {
-            let date_text_dimensions = date_text.bounding_box();
-            Rectangle::new(date_text_dimensions.top_left, date_text_dimensions.size)
-                .into_styled(PrimitiveStyle::with_fill(self.bg_color))
-                .draw(target)?;
-            date_text.draw(target)?;
+        canvas.fill_disc(self.center(), CAP_RADIUS, self.fg_color);
+        canvas.fill_disc(self.center(), CAP_RADIUS / 2.0, Hand::Second.color());
+        for (element, area) in layout {
+            match element {
+                Element::Hand(..) => {}
+                Element::Text(value) => {
+                    self.text(value, area.top_left.x, area.top_left.y).draw(canvas)?;
+                }
+                Element::Cjk(value) => self.draw_cjk(canvas, value)?,
+            }
         }
         Ok(())
     }
-    pub fn update(&mut self, display: &mut D) -> anyhow::Result<(), D::Error>
+}
+
+impl<D: DrawTarget<Color = Bgr565>> WatchFace<D> for Clock<'_, D> {
+    fn reset(&mut self) {
+        self.shown = None;
+    }
+    /// re-renders only the areas of the elements that differ from the frame on screen
+    fn update(&mut self, display: &mut D, date: &DateTime<FixedOffset>) -> anyhow::Result<(), D::Error>
     {
-        let date = Local::now().with_timezone(&FixedOffset::east_opt(self.date_fixed_offset).unwrap());
-        self.draw_face(display, self.fg_color)?;
-        self.draw_hand(display, date.hour(), -20, Hand::Hour, Bgr565::RED)?;
-        self.draw_hand(display, date.minute(), -15, Hand::Minute, Bgr565::GREEN)?;
-        self.draw_hand(display, date.second(), -10, Hand::Second, Bgr565::BLUE)?;
-        self.draw_text(display, &date)?;
-        self.text.update(&date);
+        let cache = DateCache::from(*date);
+        let layout = self.layout(&cache);
+        let mut compositor = Compositor::new(Rectangle::new(Point::zero(), Size::new(self.width, self.height)));
+        match self.shown {
+            Some(ref shown) if shown.len() == layout.len() => {
+                for ((old, old_area), (new, new_area)) in shown.iter().zip(&layout) {
+                    if old != new || old_area != new_area {
+                        compositor.add(*old_area);
+                        compositor.add(*new_area);
+                    }
+                }
+            }
+            // nothing drawn yet, or a frame of another shape
+            ref shown => {
+                compositor.add(self.face.bounding_box().offset(1));
+                for (_, area) in shown.iter().flatten().chain(&layout) {
+                    compositor.add(*area);
+                }
+            }
+        }
+        compositor.flush(display, self.bg_color, |canvas| {
+            self.compose(canvas, &layout).unwrap_or_else(|never| match never {})
+        })?;
+        self.shown = Some(layout);
         Ok(())
     }
 }
@@ -0,0 +1,62 @@
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::pixelcolor::Bgr565;
+use embedded_graphics::primitives::Rectangle;
+
+use crate::display::canvas::Canvas;
+
+/// smallest rectangle holding both, an empty one holds nothing
+pub fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    match (a.is_zero_sized(), b.is_zero_sized()) {
+        (true, _) => b,
+        (_, true) => a,
+        _ => {
+            let top_left = a.top_left.component_min(b.top_left);
+            let bottom_right = (a.top_left + a.size).component_max(b.top_left + b.size);
+            Rectangle::with_corners(top_left, bottom_right - Point::new(1, 1))
+        }
+    }
+}
+
+/// collects the areas of the screen that changed and re-renders each of them off-screen,
+/// overlapping areas are merged first so no pixel is rendered or sent twice
+pub struct Compositor {
+    screen: Rectangle,
+    regions: Vec<Rectangle>,
+}
+
+impl Compositor {
+    pub fn new(screen: Rectangle) -> Self {
+        Self { screen, regions: Vec::new() }
+    }
+
+    pub fn add(&mut self, area: Rectangle) {
+        let mut area = self.screen.intersection(&area);
+        if area.is_zero_sized() {
+            return;
+        }
+        // a merged region can reach others it did not touch before
+        while let Some(index) = self.regions
+            .iter()
+            .position(|region| !region.intersection(&area).is_zero_sized()) {
+            area = union(area, self.regions.swap_remove(index));
+        }
+        self.regions.push(area);
+    }
+
+    /// `render` draws the whole scene onto a canvas cleared to `bg`, the canvas keeps only its region
+    /// which goes to `target` in a single transfer
+    pub fn flush<D: DrawTarget<Color = Bgr565>>(
+        &mut self,
+        target: &mut D,
+        bg: Bgr565,
+        mut render: impl FnMut(&mut Canvas),
+    ) -> Result<(), D::Error> {
+        for region in self.regions.drain(..) {
+            let mut canvas = Canvas::new(region, bg);
+            render(&mut canvas);
+            canvas.flush(target)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{DateTime, FixedOffset, Timelike};
+use embedded_graphics::Drawable;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point};
+use embedded_graphics::mono_font::ascii::{FONT_6X10, FONT_8X13};
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Bgr565;
+use embedded_graphics::prelude::{Primitive, RgbColor};
+use embedded_graphics::primitives::{Circle, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+
+use crate::display::face::{formats, WatchFace};
+use crate::fs::config::HourFormat;
+
+/// bits each bcd column needs: hour tens, hour, minute tens, minute, second tens, second
+const BITS: [u32; 6] = [2, 4, 3, 4, 3, 4];
+const ROWS: u32 = 4;
+const PITCH: i32 = 16;
+const DOT: u32 = 12;
+/// extra space between the hour, minute and second pairs
+const PAIR_GAP: i32 = 6;
+
+/// binary coded decimal, one column of dots per digit with 8 4 2 1 from the top
+pub struct BinaryFace<'a> {
+    width: u32,
+    bg_color: Bgr565,
+    fg_color: Bgr565,
+    dim_color: Bgr565,
+    text_font: MonoTextStyle<'a, Bgr565>,
+    hour_format: HourFormat,
+    columns: [Option<u32>; 6],
+    labels: bool,
+    readout_box: Rectangle,
+}
+
+impl BinaryFace<'_> {
+    pub fn new(width: u32, _height: u32, bg: Bgr565, fg: Bgr565) -> Self {
+        Self {
+            width,
+            bg_color: bg,
+            fg_color: fg,
+            dim_color: Bgr565::new(fg.r() / 6, fg.g() / 6, fg.b() / 6),
+            text_font: MonoTextStyle::new(&FONT_8X13, fg),
+            hour_format: formats().0,
+            columns: [None; 6],
+            labels: false,
+            readout_box: Rectangle::zero(),
+        }
+    }
+
+    fn left(&self) -> i32 {
+        (self.width as i32 - (BITS.len() as i32 * PITCH + 2 * PAIR_GAP)) / 2
+    }
+
+    fn column_x(&self, column: usize) -> i32 {
+        self.left() + column as i32 * PITCH + (column / 2) as i32 * PAIR_GAP
+    }
+
+    /// top left of the dot for `bit` (3 is the 8) in `column`
+    fn dot(&self, column: usize, bit: u32) -> Point {
+        Point::new(self.column_x(column), 20 + (ROWS - 1 - bit) as i32 * PITCH)
+    }
+
+    fn draw_column<D: DrawTarget<Color = Bgr565>>(&self, target: &mut D, column: usize, value: u32) -> Result<(), D::Error> {
+        for bit in 0..BITS[column] {
+            let color = match value & (1 << bit) {
+                0 => self.dim_color,
+                _ => self.fg_color,
+            };
+            Circle::new(self.dot(column, bit), DOT)
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(target)?;
+        }
+        Ok(())
+    }
+
+    fn draw_labels<D: DrawTarget<Color = Bgr565>>(&self, target: &mut D) -> Result<(), D::Error> {
+        let style = MonoTextStyle::new(&FONT_6X10, self.dim_color);
+        for (pair, label) in ["H", "M", "S"].iter().enumerate() {
+            let x = (self.column_x(pair * 2) + self.column_x(pair * 2 + 1) + DOT as i32) / 2;
+            Text::with_text_style(label, Point::new(x, 20 + ROWS as i32 * PITCH), style,
+                                  TextStyleBuilder::new().alignment(Alignment::Center).baseline(Baseline::Top).build())
+                .draw(target)?;
+        }
+        for bit in 0..ROWS {
+            Text::with_text_style(&format!("{}", 1 << bit), self.dot(0, bit) + Point::new(-4, DOT as i32 / 2), style,
+                                  TextStyleBuilder::new().alignment(Alignment::Right).baseline(Baseline::Middle).build())
+                .draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: DrawTarget<Color = Bgr565>> WatchFace<D> for BinaryFace<'_> {
+    fn reset(&mut self) {
+        self.columns = [None; 6];
+        self.labels = false;
+        self.readout_box = Rectangle::zero();
+    }
+
+    fn update(&mut self, display: &mut D, date: &DateTime<FixedOffset>) -> Result<(), D::Error> {
+        if !self.labels {
+            self.draw_labels(display)?;
+            self.labels = true;
+        }
+        let hour = match self.hour_format {
+            HourFormat::H24 => date.hour(),
+            HourFormat::H12 => date.hour12().1,
+        };
+        let second = date.second() + date.nanosecond() / 1_000_000_000;
+        let values = [hour / 10, hour % 10, date.minute() / 10, date.minute() % 10, second / 10, second % 10];
+        let mut changed = false;
+        for (column, value) in values.iter().enumerate() {
+            if self.columns[column] != Some(*value) {
+                self.draw_column(display, column, *value)?;
+                self.columns[column] = Some(*value);
+                changed = true;
+            }
+        }
+        if changed {
+            self.readout_box
+                .into_styled(PrimitiveStyle::with_fill(self.bg_color))
+                .draw(display)?;
+            let readout = format!("{}", date.format(self.hour_format.time()));
+            let text = Text::with_text_style(&readout, Point::new(self.width as i32 / 2, 106), self.text_font,
+                                             TextStyleBuilder::new().alignment(Alignment::Center).baseline(Baseline::Top).build());
+            text.draw(display)?;
+            self.readout_box = text.bounding_box();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Datelike, FixedOffset, Timelike};
+use embedded_graphics::Drawable;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::mono_font::ascii::FONT_8X13;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Bgr565;
+use embedded_graphics::prelude::{Primitive, RgbColor};
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+
+use crate::display::face::{formats, WatchFace};
+use crate::fs::config::HourFormat;
+
+/// lit segments of 0 to 9, bit 0 is segment a through bit 6 for g
+const DIGITS: [u8; 10] = [0x3f, 0x06, 0x5b, 0x4f, 0x66, 0x6d, 0x7d, 0x07, 0x7f, 0x6f];
+
+/// a seven segment digit `width` wide, segments `thickness` thick
+struct Segments {
+    width: u32,
+    thickness: u32,
+    /// length of the vertical segments
+    half: u32,
+}
+
+impl Segments {
+    const fn new(width: u32, height: u32, thickness: u32) -> Self {
+        Self { width, thickness, half: (height - 3 * thickness) / 2 }
+    }
+    fn height(&self) -> u32 {
+        3 * self.thickness + 2 * self.half
+    }
+    fn segment(&self, origin: Point, index: usize) -> Rectangle {
+        let (w, t, half) = (self.width as i32, self.thickness as i32, self.half as i32);
+        let (x, y, size) = match index {
+            0 => (t, 0, Size::new(self.width - 2 * self.thickness, self.thickness)),
+            1 => (w - t, t, Size::new(self.thickness, self.half)),
+            2 => (w - t, 2 * t + half, Size::new(self.thickness, self.half)),
+            3 => (t, 2 * t + 2 * half, Size::new(self.width - 2 * self.thickness, self.thickness)),
+            4 => (0, 2 * t + half, Size::new(self.thickness, self.half)),
+            5 => (0, t, Size::new(self.thickness, self.half)),
+            _ => (t, t + half, Size::new(self.width - 2 * self.thickness, self.thickness)),
+        };
+        Rectangle::new(origin + Point::new(x, y), size)
+    }
+    /// every segment is drawn, unlit ones dimmed, so nothing has to be cleared first
+    fn draw<D: DrawTarget<Color = Bgr565>>(&self, target: &mut D, origin: Point, digit: u32, on: Bgr565, off: Bgr565) -> Result<(), D::Error> {
+        let mask = DIGITS[digit as usize % 10];
+        for index in 0..7 {
+            let color = match mask & (1 << index) {
+                0 => off,
+                _ => on,
+            };
+            self.segment(origin, index)
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+const LARGE: Segments = Segments::new(24, 46, 5);
+const SMALL: Segments = Segments::new(12, 22, 3);
+
+/// full screen seven segment display, hh:mm large with seconds and the date below
+pub struct DigitalFace<'a> {
+    width: u32,
+    height: u32,
+    bg_color: Bgr565,
+    fg_color: Bgr565,
+    dim_color: Bgr565,
+    text_font: MonoTextStyle<'a, Bgr565>,
+    hour_format: HourFormat,
+    date_format: String,
+    /// h h m m s s as last drawn
+    digits: [Option<u32>; 6],
+    colon: Option<bool>,
+    day: Option<u32>,
+    date_box: Rectangle,
+    meridiem: Option<bool>,
+}
+
+impl DigitalFace<'_> {
+    pub fn new(width: u32, height: u32, bg: Bgr565, fg: Bgr565) -> Self {
+        let (hour_format, date_format) = formats();
+        Self {
+            width,
+            height,
+            bg_color: bg,
+            fg_color: fg,
+            dim_color: Bgr565::new(fg.r() / 6, fg.g() / 6, fg.b() / 6),
+            text_font: MonoTextStyle::new(&FONT_8X13, fg),
+            hour_format,
+            date_format,
+            digits: [None; 6],
+            colon: None,
+            day: None,
+            date_box: Rectangle::zero(),
+            meridiem: None,
+        }
+    }
+
+    /// top left corner of digit `index`, the large ones centered on the screen
+    fn digit_origin(&self, index: usize) -> Point {
+        let large_top = (self.height - LARGE.height()) as i32 / 2;
+        let small_top = large_top + LARGE.height() as i32 + 8;
+        let center = self.width as i32 / 2;
+        let (w, gap) = (LARGE.width as i32, 4);
+        match index {
+            0 => Point::new(center - 6 - 2 * w - gap, large_top),
+            1 => Point::new(center - 6 - w, large_top),
+            2 => Point::new(center + 6, large_top),
+            3 => Point::new(center + 6 + w + gap, large_top),
+            4 => Point::new(center - SMALL.width as i32 - 2, small_top),
+            _ => Point::new(center + 2, small_top),
+        }
+    }
+
+    fn draw_colon<D: DrawTarget<Color = Bgr565>>(&self, target: &mut D, lit: bool) -> Result<(), D::Error> {
+        let color = if lit { self.fg_color } else { self.dim_color };
+        let top = (self.height - LARGE.height()) as i32 / 2;
+        for y in [LARGE.height() as i32 / 3, LARGE.height() as i32 * 2 / 3] {
+            Rectangle::with_center(Point::new(self.width as i32 / 2, top + y), Size::new(LARGE.thickness, LARGE.thickness))
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(target)?;
+        }
+        Ok(())
+    }
+
+    fn draw_text<D: DrawTarget<Color = Bgr565>>(&self, target: &mut D, value: &str, position: Point, alignment: Alignment) -> Result<Rectangle, D::Error> {
+        let text = Text::with_text_style(
+            value,
+            position,
+            self.text_font,
+            TextStyleBuilder::new()
+                .alignment(alignment)
+                .baseline(Baseline::Top)
+                .build(),
+        );
+        text.draw(target)?;
+        Ok(text.bounding_box())
+    }
+}
+
+impl<D: DrawTarget<Color = Bgr565>> WatchFace<D> for DigitalFace<'_> {
+    fn reset(&mut self) {
+        self.digits = [None; 6];
+        self.colon = None;
+        self.day = None;
+        self.date_box = Rectangle::zero();
+        self.meridiem = None;
+    }
+
+    fn update(&mut self, display: &mut D, date: &DateTime<FixedOffset>) -> Result<(), D::Error> {
+        let hour = match self.hour_format {
+            HourFormat::H24 => date.hour(),
+            HourFormat::H12 => date.hour12().1,
+        };
+        let second = date.second() + date.nanosecond() / 1_000_000_000;
+        let digits = [hour / 10, hour % 10, date.minute() / 10, date.minute() % 10, second / 10, second % 10];
+        for (index, digit) in digits.iter().enumerate() {
+            if self.digits[index] == Some(*digit) {
+                continue;
+            }
+            let segments = if index < 4 { &LARGE } else { &SMALL };
+            segments.draw(display, self.digit_origin(index), *digit, self.fg_color, self.dim_color)?;
+            self.digits[index] = Some(*digit);
+        }
+        let colon = second % 2 == 0;
+        if self.colon != Some(colon) {
+            self.draw_colon(display, colon)?;
+            self.colon = Some(colon);
+        }
+        if self.day != Some(date.ordinal()) {
+            self.date_box
+                .into_styled(PrimitiveStyle::with_fill(self.bg_color))
+                .draw(display)?;
+            let value = format!("{}", date.format(&self.date_format));
+            self.date_box = self.draw_text(display, &value, Point::new(self.width as i32 / 2, 10), Alignment::Center)?;
+            self.day = Some(date.ordinal());
+        }
+        if self.hour_format == HourFormat::H12 {
+            let pm = date.hour12().0;
+            if self.meridiem != Some(pm) {
+                let origin = self.digit_origin(5) + Point::new(SMALL.width as i32 + 8, (SMALL.height() as i32 - 13) / 2);
+                let area = Rectangle::new(origin, Size::new(16, 13));
+                area.into_styled(PrimitiveStyle::with_fill(self.bg_color)).draw(display)?;
+                self.draw_text(display, if pm { "PM" } else { "AM" }, origin, Alignment::Left)?;
+                self.meridiem = Some(pm);
+            }
+        }
+        Ok(())
+    }
+}
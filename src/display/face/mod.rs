@@ -0,0 +1,177 @@
+use chrono::{DateTime, Duration, FixedOffset, Timelike, Utc};
+use chrono::format::{Item, StrftimeItems};
+use embedded_graphics::Drawable;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::pixelcolor::Bgr565;
+use embedded_graphics::prelude::{Primitive, RgbColor};
+use embedded_graphics::primitives::{Circle, PrimitiveStyle};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::display::clock::Clock;
+use crate::display::face::binary::BinaryFace;
+use crate::display::face::digital::DigitalFace;
+use crate::display::face::word::WordFace;
+use crate::display::pacer::Pacer;
+use crate::fs::config::{CONFIG, HourFormat};
+use crate::fs::store;
+use crate::net::{in_leap_second, sync_status};
+use crate::tz;
+
+pub mod binary;
+pub mod digital;
+pub mod word;
+
+const FACE_KEY: &str = "watch_face";
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// one way of drawing the clock page
+pub trait WatchFace<D: DrawTarget<Color = Bgr565>> {
+    /// the screen was cleared, draw everything on the next update
+    fn reset(&mut self);
+    /// bring the screen up to `date`, drawing only what changed since the last call
+    fn update(&mut self, display: &mut D, date: &DateTime<FixedOffset>) -> Result<(), D::Error>;
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FaceKind {
+    #[default]
+    Analog,
+    Digital,
+    Binary,
+    Word,
+}
+
+impl FaceKind {
+    const ALL: [FaceKind; 4] = [FaceKind::Analog, FaceKind::Digital, FaceKind::Binary, FaceKind::Word];
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|kind| kind == self).unwrap_or(0)
+    }
+    pub fn next(&self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+    pub fn prev(&self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// `hour_format` and `date_format` from the config, a broken date pattern falls back to the default
+/// since chrono panics while printing it
+pub fn formats() -> (HourFormat, String) {
+    let (hour_format, date_format) = match CONFIG.as_ref() {
+        None => (HourFormat::default(), DEFAULT_DATE_FORMAT.into()),
+        Some(config) => (config.hour_format, config.date_format.clone()),
+    };
+    match StrftimeItems::new(&date_format).any(|item| item == Item::Error) {
+        true => {
+            error!("invalid date_format: {}", date_format);
+            (hour_format, DEFAULT_DATE_FORMAT.into())
+        }
+        false => (hour_format, date_format),
+    }
+}
+
+/// all faces of the clock page, the selected one survives a reboot
+pub struct Faces<'a, D: DrawTarget<Color = Bgr565>> {
+    kind: FaceKind,
+    faces: Vec<Box<dyn WatchFace<D> + 'a>>,
+    bg_color: Bgr565,
+    /// without a sync for this long the page shows the unsynced marker
+    sync_max_age: Duration,
+    unsynced: Option<bool>,
+    /// `sweep_fps` from the config when the second hand sweeps
+    sweep_fps: Option<u32>,
+    pacer: Pacer,
+}
+
+impl<'a, D: DrawTarget<Color = Bgr565> + 'a> Faces<'a, D> {
+    pub fn new(width: u32, height: u32, size: u32, bg: Bgr565, fg: Bgr565) -> Self {
+        let sync_time_interval = CONFIG.as_ref().map_or(3600, |config| config.sync_time_interval);
+        let sweep_fps = CONFIG.as_ref().and_then(|config| config.sweep_second_hand.then_some(config.sweep_fps));
+        let kind = store::load(FACE_KEY).unwrap_or_default();
+        // same order as `FaceKind::ALL`
+        let faces: Vec<Box<dyn WatchFace<D> + 'a>> = vec![
+            Box::new(Clock::new(width, height, size, bg, fg)),
+            Box::new(DigitalFace::new(width, height, bg, fg)),
+            Box::new(BinaryFace::new(width, height, bg, fg)),
+            Box::new(WordFace::new(width, height, bg, fg)),
+        ];
+        Self {
+            kind,
+            faces,
+            bg_color: bg,
+            sync_max_age: Duration::seconds(2 * sync_time_interval as i64),
+            unsynced: None,
+            sweep_fps,
+            pacer: Pacer::new(Self::fps(kind, sweep_fps)),
+        }
+    }
+
+    /// only the analog face has anything to animate between seconds
+    fn fps(kind: FaceKind, sweep_fps: Option<u32>) -> u32 {
+        match (kind, sweep_fps) {
+            (FaceKind::Analog, Some(fps)) => fps,
+            _ => 1,
+        }
+    }
+
+    /// clears the display for the face to draw everything on the next update
+    pub fn reset(&mut self, display: &mut D) -> Result<(), D::Error> {
+        display.clear(self.bg_color)?;
+        self.faces[self.kind.index()].reset();
+        self.unsynced = None;
+        self.pacer.reset();
+        Ok(())
+    }
+
+    fn select(&mut self, display: &mut D, kind: FaceKind) -> Result<(), D::Error> {
+        self.kind = kind;
+        self.pacer = Pacer::new(Self::fps(kind, self.sweep_fps));
+        if let Err(e) = store::save(FACE_KEY, &kind) {
+            error!("save watch face failed: {}", e);
+        }
+        self.reset(display)
+    }
+    pub fn next(&mut self, display: &mut D) -> Result<(), D::Error> {
+        self.select(display, self.kind.next())
+    }
+    pub fn prev(&mut self, display: &mut D) -> Result<(), D::Error> {
+        self.select(display, self.kind.prev())
+    }
+
+    /// small red dot in the top left corner while the time is not trusted
+    fn draw_sync_indicator(&mut self, target: &mut D, unsynced: bool) -> Result<(), D::Error> {
+        if self.unsynced == Some(unsynced) {
+            return Ok(());
+        }
+        let color = match unsynced {
+            true => Bgr565::RED,
+            false => self.bg_color,
+        };
+        Circle::new(Point::new(2, 2), 6)
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(target)?;
+        self.unsynced = Some(unsynced);
+        Ok(())
+    }
+
+    /// whether the next frame is due, on the second boundary or the next sweep frame
+    pub fn due(&self) -> bool {
+        self.pacer.due(&Utc::now())
+    }
+
+    pub fn update(&mut self, display: &mut D) -> Result<(), D::Error> {
+        let now = Utc::now();
+        let mut date = tz::local(&now);
+        if in_leap_second(&now) {
+            date = (date - Duration::seconds(1)).with_nanosecond(1_000_000_000 + date.nanosecond()).unwrap_or(date);
+        }
+        self.faces[self.kind.index()].update(display, &date)?;
+        self.draw_sync_indicator(display, sync_status().is_stale(&now, self.sync_max_age))?;
+        let end = Utc::now();
+        self.pacer.done(&end, end - now);
+        Ok(())
+    }
+}
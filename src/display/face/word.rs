@@ -0,0 +1,171 @@
+use chrono::{DateTime, FixedOffset, Timelike};
+use embedded_graphics::Drawable;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Bgr565;
+use embedded_graphics::prelude::{Primitive, RgbColor};
+use embedded_graphics::primitives::{Circle, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+
+use crate::display::face::WatchFace;
+
+const GRID: [&str; 10] = [
+    "ITLISASAMPM",
+    "ACQUARTERDC",
+    "TWENTYFIVEX",
+    "HALFSTENFTO",
+    "PASTERUNINE",
+    "ONESIXTHREE",
+    "FOURFIVETWO",
+    "EIGHTELEVEN",
+    "SEVENTWELVE",
+    "TENSEOCLOCK",
+];
+const COLUMNS: usize = 11;
+const CELL: Size = Size::new(10, 11);
+
+/// (row, column, length) of a word in `GRID`
+type Word = (usize, usize, usize);
+
+const IT: Word = (0, 0, 2);
+const IS: Word = (0, 3, 2);
+const AM: Word = (0, 7, 2);
+const PM: Word = (0, 9, 2);
+const A: Word = (1, 0, 1);
+const QUARTER: Word = (1, 2, 7);
+const TWENTY: Word = (2, 0, 6);
+const FIVE_MINUTES: Word = (2, 6, 4);
+const HALF: Word = (3, 0, 4);
+const TEN_MINUTES: Word = (3, 5, 3);
+const TO: Word = (3, 9, 2);
+const PAST: Word = (4, 0, 4);
+const OCLOCK: Word = (9, 5, 6);
+/// one to twelve
+const HOURS: [Word; 12] = [
+    (5, 0, 3), (6, 8, 3), (5, 6, 5), (6, 0, 4), (6, 4, 4), (5, 3, 3),
+    (8, 0, 5), (7, 0, 5), (4, 7, 4), (9, 0, 3), (7, 5, 6), (8, 5, 6),
+];
+
+/// words lit for `hour` (0 to 23) and `minute`, which is rounded down to five
+fn words(hour: u32, minute: u32) -> Vec<Word> {
+    let mut words = vec![IT, IS, if hour < 12 { AM } else { PM }];
+    let (before, after): (&[Word], bool) = match minute / 5 {
+        0 => (&[], false),
+        1 => (&[FIVE_MINUTES, PAST], false),
+        2 => (&[TEN_MINUTES, PAST], false),
+        3 => (&[A, QUARTER, PAST], false),
+        4 => (&[TWENTY, PAST], false),
+        5 => (&[TWENTY, FIVE_MINUTES, PAST], false),
+        6 => (&[HALF, PAST], false),
+        7 => (&[TWENTY, FIVE_MINUTES, TO], true),
+        8 => (&[TWENTY, TO], true),
+        9 => (&[A, QUARTER, TO], true),
+        10 => (&[TEN_MINUTES, TO], true),
+        _ => (&[FIVE_MINUTES, TO], true),
+    };
+    words.extend_from_slice(before);
+    let hour = (hour + after as u32) % 12;
+    words.push(HOURS[(hour + 11) as usize % 12]);
+    if minute < 5 {
+        words.push(OCLOCK);
+    }
+    words
+}
+
+/// one bit per letter of `GRID`
+fn lit(words: &[Word]) -> [u16; GRID.len()] {
+    let mut rows = [0u16; GRID.len()];
+    for (row, column, length) in words {
+        rows[*row] |= ((1u16 << length) - 1) << column;
+    }
+    rows
+}
+
+/// english word clock, a letter grid with the time spelled out in five minute steps
+/// and a dot in a corner for each minute past them
+pub struct WordFace {
+    width: u32,
+    height: u32,
+    bg_color: Bgr565,
+    fg_color: Bgr565,
+    dim_color: Bgr565,
+    rows: Option<[u16; GRID.len()]>,
+    dots: Option<u32>,
+}
+
+impl WordFace {
+    pub fn new(width: u32, height: u32, bg: Bgr565, fg: Bgr565) -> Self {
+        Self {
+            width,
+            height,
+            bg_color: bg,
+            fg_color: fg,
+            dim_color: Bgr565::new(fg.r() / 5, fg.g() / 5, fg.b() / 5),
+            rows: None,
+            dots: None,
+        }
+    }
+
+    fn origin(&self) -> Point {
+        Point::new(
+            (self.width - CELL.width * COLUMNS as u32) as i32 / 2,
+            (self.height - CELL.height * GRID.len() as u32) as i32 / 2,
+        )
+    }
+
+    fn draw_letter<D: DrawTarget<Color = Bgr565>>(&self, target: &mut D, row: usize, column: usize, lit: bool) -> Result<(), D::Error> {
+        let top_left = self.origin() + Point::new(column as i32 * CELL.width as i32, row as i32 * CELL.height as i32);
+        Rectangle::new(top_left, CELL)
+            .into_styled(PrimitiveStyle::with_fill(self.bg_color))
+            .draw(target)?;
+        let color = if lit { self.fg_color } else { self.dim_color };
+        let letter = &GRID[row][column..column + 1];
+        Text::with_text_style(letter, top_left + Point::new(CELL.width as i32 / 2, CELL.height as i32 / 2),
+                              MonoTextStyle::new(&FONT_6X10, color),
+                              TextStyleBuilder::new().alignment(Alignment::Center).baseline(Baseline::Middle).build())
+            .draw(target)?;
+        Ok(())
+    }
+
+    /// `count` of the four corner dots lit, clockwise from the top right
+    fn draw_dots<D: DrawTarget<Color = Bgr565>>(&self, target: &mut D, count: u32) -> Result<(), D::Error> {
+        let (right, bottom) = (self.width as i32 - 6, self.height as i32 - 6);
+        // the top left corner holds the sync marker, the last dot sits below it
+        for (index, corner) in [Point::new(right, 2), Point::new(right, bottom), Point::new(2, bottom), Point::new(2, 10)].iter().enumerate() {
+            let color = if (index as u32) < count { self.fg_color } else { self.bg_color };
+            Circle::new(*corner, 4)
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(target)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: DrawTarget<Color = Bgr565>> WatchFace<D> for WordFace {
+    fn reset(&mut self) {
+        self.rows = None;
+        self.dots = None;
+    }
+
+    fn update(&mut self, display: &mut D, date: &DateTime<FixedOffset>) -> Result<(), D::Error> {
+        let rows = lit(&words(date.hour(), date.minute()));
+        for (row, bits) in rows.iter().enumerate() {
+            let old = self.rows.map(|rows| rows[row]);
+            for column in 0..COLUMNS {
+                let on = bits & (1 << column) != 0;
+                if old.map(|old| old & (1 << column) != 0) != Some(on) {
+                    self.draw_letter(display, row, column, on)?;
+                }
+            }
+        }
+        self.rows = Some(rows);
+        let dots = date.minute() % 5;
+        if self.dots != Some(dots) {
+            self.draw_dots(display, dots)?;
+            self.dots = Some(dots);
+        }
+        Ok(())
+    }
+}
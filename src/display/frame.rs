@@ -0,0 +1,52 @@
+use std::convert::Infallible;
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{OriginDimensions, Size};
+use embedded_graphics::pixelcolor::{Bgr565, Rgb888};
+use embedded_graphics::prelude::RgbColor;
+use embedded_graphics::Pixel;
+use slint::{Image, Rgb8Pixel, SharedPixelBuffer};
+
+/// pixels of a Slint image that embedded-graphics can draw on, so the watch faces render
+/// into the Slint scene and the backend stays the only one writing to the panel
+pub struct Frame {
+    buffer: SharedPixelBuffer<Rgb8Pixel>,
+}
+
+impl Frame {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { buffer: SharedPixelBuffer::new(width, height) }
+    }
+
+    /// shares the pixels with the image, drawing on the frame while the image is still
+    /// shown copies them first
+    pub fn image(&self) -> Image {
+        Image::from_rgb8(self.buffer.clone())
+    }
+}
+
+impl OriginDimensions for Frame {
+    fn size(&self) -> Size {
+        Size::new(self.buffer.width(), self.buffer.height())
+    }
+}
+
+impl DrawTarget for Frame {
+    type Color = Bgr565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (width, height) = (self.buffer.width() as i32, self.buffer.height() as i32);
+        let slice = self.buffer.make_mut_slice();
+        for Pixel(point, color) in pixels {
+            if (0..width).contains(&point.x) && (0..height).contains(&point.y) {
+                let color = Rgb888::from(color);
+                slice[(point.y * width + point.x) as usize] = Rgb8Pixel::new(color.r(), color.g(), color.b());
+            }
+        }
+        Ok(())
+    }
+}
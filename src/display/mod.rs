@@ -19,7 +19,12 @@ use st7735r::{Orientation, ST7735};
 
 use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
 
+pub mod canvas;
 pub mod clock;
+pub mod compositor;
+pub mod face;
+pub mod frame;
+pub mod pacer;
 pub mod st7735r;
 mod backend;
 pub mod ui;
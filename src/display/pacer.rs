@@ -0,0 +1,46 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// frame lengths in milliseconds that fit a whole number of times into a second,
+/// so every second starts on a frame
+const PERIODS: [i64; 10] = [20, 25, 40, 50, 100, 125, 200, 250, 500, 1000];
+
+/// decides when the next frame is drawn, frames sit on slots counted from the start of
+/// the second in system time rather than on a tick counter that drifts against it
+pub struct Pacer {
+    /// index into `PERIODS` of the rate asked for
+    fastest: usize,
+    period: usize,
+    /// unix milliseconds of the next slot, `None` draws right away
+    next: Option<i64>,
+}
+
+impl Pacer {
+    pub fn new(fps: u32) -> Self {
+        let wanted = 1000 / fps.clamp(1, 50) as i64;
+        let fastest = PERIODS.iter().position(|period| *period >= wanted).unwrap_or(PERIODS.len() - 1);
+        Self { fastest, period: fastest, next: None }
+    }
+
+    pub fn reset(&mut self) {
+        self.period = self.fastest;
+        self.next = None;
+    }
+
+    pub fn due(&self, now: &DateTime<Utc>) -> bool {
+        self.next.map_or(true, |next| now.timestamp_millis() >= next)
+    }
+
+    /// a frame finished at `now` after taking `cost`, the next one goes on the first slot
+    /// after it, so a slow frame skips slots instead of piling them up
+    pub fn done(&mut self, now: &DateTime<Utc>, cost: Duration) {
+        let cost = cost.num_milliseconds();
+        // fall back to a lower rate while frames eat most of their slot, return once they are cheap again
+        if cost * 4 > PERIODS[self.period] * 3 && self.period < PERIODS.len() - 1 {
+            self.period += 1;
+        } else if self.period > self.fastest && cost * 2 < PERIODS[self.period - 1] {
+            self.period -= 1;
+        }
+        let period = PERIODS[self.period];
+        self.next = Some((now.timestamp_millis().div_euclid(period) + 1) * period);
+    }
+}
@@ -1,32 +1,73 @@
 use std::net::Ipv4Addr;
 use std::sync::mpsc::Receiver;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
 use embedded_graphics_core::draw_target::DrawTarget;
 use embedded_graphics_core::geometry::OriginDimensions;
 use embedded_graphics_core::pixelcolor::Bgr565;
 use esp_idf_hal::sys::{heap_caps_print_heap_info, MALLOC_CAP_DEFAULT};
-use slint::Color;
+use slint::{Color, Image, ModelRc, VecModel};
 
 use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use crate::alarm::{self, Alarm, Scheduler};
+use crate::alarm::chime::Chimes;
 use crate::display::backend::EspBackend;
-use crate::display::clock::Clock;
-use crate::net::net_info;
+use crate::display::face::Faces;
+use crate::display::frame::Frame;
+use crate::fs::config::{Buzzer, CONFIG, HourFormat};
+use crate::timer::countdown::Countdown;
+use crate::timer::format_duration;
+use crate::timer::pomodoro::{Phase, Pomodoro};
+use crate::timer::stopwatch::Stopwatch;
+use crate::net::{net_info, sync_status};
+use crate::sound;
+use crate::tz;
+use crate::tz::world::city_times;
 use crate::utils::{DeviceID, MemInfo};
 use crate::utils::state::{Btn, State};
 
 slint::include_modules!();
 
+/// what fits of the last sync error on the profile page
+const MAX_ERROR_CHARS: usize = 60;
+
+fn sync_info() -> String {
+    let status = sync_status();
+    let time = |time: Option<DateTime<Utc>>| match time {
+        None => String::from("-"),
+        Some(time) => tz::format(&time, "%m-%d %H:%M:%S"),
+    };
+    let ms = |value: Option<chrono::Duration>| match value {
+        None => String::from("-"),
+        Some(value) => format!("{}ms", value.num_milliseconds()),
+    };
+    let history = status.history.iter()
+        .map(|offset| offset.map_or(String::from("x"), |offset| format!("{:+}", offset.num_milliseconds())))
+        .collect::<Vec<String>>()
+        .join(" ");
+    // last, a long message wraps without pushing the rest off the page
+    let error = status.last_error.map_or(String::from("-"), |error| error.chars().take(MAX_ERROR_CHARS).collect());
+    format!("Sync: {}\nFrom: {}\nOffset: {}\nDelay: {}\nFails: {}\nNext: {}\nDrift: {}\nHist: {}\nError: {}",
+            time(status.last_success), status.source.unwrap_or(String::from("-")),
+            ms(status.offset), ms(status.delay), status.failures, time(status.next_sync),
+            status.drift_ppm.map_or(String::from("-"), |ppm| format!("{:+.2}ppm", ppm)), history, error)
+}
+
+/// profile pages, Ok flips through them
+const INFO_PAGES: usize = 2;
+
 #[inline(always)]
-pub fn show_info(strong: MainWindow) {
+pub fn show_info(strong: MainWindow, page: usize) {
     let device_id = DeviceID::get();
     let mut meminfo = MemInfo::new();
     meminfo.fetch();
     let (free, total) = meminfo.kb();
     let mem_info = format!(": {:.2}/{:.2}", free, total);
-    let info = match net_info() {
-        None => format!("Mac: {}", device_id),
-        Some(info) => format!("Mac: {}\nIp: {}\nNet: {}/{}\nDns: {}\nMem: {}", device_id, info.ip,
+    let info = match (page, net_info()) {
+        (1, _) => sync_info(),
+        (_, None) => format!("Mac: {}", device_id),
+        (_, Some(info)) => format!("Mac: {}\nIp: {}\nNet: {}/{}\nDns: {}\nMem: {}", device_id, info.ip,
                               info.subnet.gateway, info.subnet.mask,
                               info.dns.unwrap_or(Ipv4Addr::new(0, 0, 0, 0)), mem_info)
     };
@@ -34,21 +75,117 @@ pub fn show_info(strong: MainWindow) {
     strong.invoke_set_visible("info".into(), true);
 }
 
+#[inline(always)]
+pub fn show_world(strong: &MainWindow) {
+    let hour_format = CONFIG.as_ref().map_or(HourFormat::default(), |config| config.hour_format);
+    let rows = city_times(&Utc::now())
+        .iter()
+        .map(|city| WorldRow {
+            name: city.name.as_str().into(),
+            time: city.local.format(hour_format.time()).to_string().into(),
+            offset: format!("{} {}", city.abbreviation, city.utc_offset()).into(),
+            day: city.is_day(),
+        })
+        .collect::<Vec<WorldRow>>();
+    strong.invoke_set_world_rows(ModelRc::new(VecModel::from(rows)));
+}
+
+const ALARM_HINT: &str = "OK stop  x2 snooze";
+const TIMER_HINT: &str = "OK stop";
+const POMODORO_HINT: &str = "OK next";
+/// laps listed under the stopwatch, Left pages through the older ones
+const LAPS_SHOWN: usize = 5;
+
+fn alarm_time(alarm: &Alarm) -> String {
+    let hour_format = CONFIG.as_ref().map_or(HourFormat::default(), |config| config.hour_format);
+    alarm.time().map_or(String::from("--:--"), |time| time.format(hour_format.hour_minute()).to_string())
+}
+
+#[inline(always)]
+pub fn show_alarms(strong: &MainWindow, scheduler: &Scheduler, selected: usize) {
+    let rows = scheduler.alarms()
+        .iter()
+        .map(|alarm| AlarmRow {
+            time: alarm_time(alarm).into(),
+            label: alarm.label.as_str().into(),
+            repeat: alarm.repeat_days().into(),
+            enabled: alarm.enabled,
+        })
+        .collect::<Vec<AlarmRow>>();
+    strong.invoke_set_alarm_rows(ModelRc::new(VecModel::from(rows)), selected as i32);
+}
+
+/// the alert of whatever still rings, an alarm before the countdown before the pomodoro,
+/// the buzzer plays its sound from the start
+fn show_alert(strong: &MainWindow, alarms: &Scheduler, countdown: &Countdown, pomodoro: &Pomodoro) {
+    let buzzer = CONFIG.as_ref().map_or(Buzzer::default(), |config| config.buzzer.clone());
+    match (alarms.ringing(), countdown.ringing(), pomodoro.ringing()) {
+        (Some(alarm), _, _) => {
+            sound::play(&buzzer.alarm, true);
+            strong.invoke_show_alert(alarm_time(alarm).into(), alarm.label.as_str().into(), ALARM_HINT.into())
+        }
+        (None, true, _) => {
+            sound::play(&buzzer.timer, true);
+            strong.invoke_show_alert(format_duration(countdown.duration(), false).into(), "Timer".into(), TIMER_HINT.into())
+        }
+        // the phase has already moved on, the alert names the one up next
+        (None, false, true) => {
+            let label = match pomodoro.phase() {
+                Phase::Work => "Back to work",
+                Phase::ShortBreak | Phase::LongBreak => "Time for a break",
+            };
+            sound::play(&buzzer.pomodoro, false);
+            strong.invoke_show_alert(pomodoro.phase().name().into(), label.into(), POMODORO_HINT.into())
+        }
+        (None, false, false) => {
+            sound::stop();
+            strong.invoke_set_visible("alert".into(), false)
+        }
+    }
+}
 
-pub fn show_ui<D>(mut display: D, receiver: Receiver<State>) -> anyhow::Result<(), D::Error>
-    where D: DrawTarget<Color=Bgr565> + OriginDimensions + 'static  + Clone, D::Error: std::fmt::Debug
+#[inline(always)]
+pub fn show_laps(strong: &MainWindow, stopwatch: &Stopwatch, lap_page: usize) {
+    let laps = stopwatch.laps()
+        .skip(lap_page * LAPS_SHOWN)
+        .take(LAPS_SHOWN)
+        .map(|(number, lap, total)| LapRow {
+            number: format!("{}", number).into(),
+            lap: format_duration(lap, true).into(),
+            total: format_duration(total, true).into(),
+        })
+        .collect::<Vec<LapRow>>();
+    strong.invoke_set_laps(ModelRc::new(VecModel::from(laps)));
+}
+
+pub fn show_ui<D>(display: D, receiver: Receiver<State>) -> anyhow::Result<(), D::Error>
+    where D: DrawTarget<Color=Bgr565> + OriginDimensions + 'static, D::Error: std::fmt::Debug
 {
-    slint::platform::set_platform(Box::new(EspBackend::new(display.clone())))
+    slint::platform::set_platform(Box::new(EspBackend::new(display)))
         .expect("backend already initialized");
     let root = MainWindow::new().unwrap();
     let strong = root.clone_strong();
     let timer = slint::Timer::default();
-    let mut clock = Clock::new(DISPLAY_WIDTH,
+    let mut frame = Frame::new(DISPLAY_WIDTH, DISPLAY_HEIGHT);
+    let mut faces = Faces::new(DISPLAY_WIDTH,
                                DISPLAY_HEIGHT, 17,
                                Bgr565::new(0, 0, 0),
                                Bgr565::new(245, 152, 66));
-    let mut clock_update_interval = 0u32;
+    // second the world clock page was last drawn for
+    let mut world_shown = 0i64;
     let mut show_clock = false;
+    let mut show_world_clock = false;
+    let mut info_page = 0usize;
+    let mut alarms = alarm::load();
+    let mut alarm_index = 0usize;
+    let mut alarm_polled = 0i64;
+    let mut chimes = Chimes::load();
+    let mut stopwatch = Stopwatch::default();
+    let mut lap_page = 0usize;
+    let mut countdown = Countdown::default();
+    let mut pomodoro = Pomodoro::load();
+    // what the stopwatch or timer page shows, they only get new text when it changes
+    let mut page_shown = String::new();
     timer.start(
         slint::TimerMode::Repeated,
         Duration::from_millis(10),
@@ -56,17 +193,84 @@ pub fn show_ui<D>(mut display: D, receiver: Receiver<State>) -> anyhow::Result<(
             match receiver.try_recv() {
                 Ok(state) => {
                     match state {
+                        // a ringing alarm takes the buttons until it is stopped or snoozed
+                        State::Btn(ref btn) if alarms.ringing().is_some() => {
+                            match btn {
+                                Btn::Ok => alarms.dismiss(),
+                                Btn::Exit => alarms.snooze(&Utc::now()),
+                                _ => {}
+                            }
+                            show_alert(&strong, &alarms, &countdown, &pomodoro);
+                        }
+                        State::Btn(_) if countdown.ringing() => {
+                            countdown.dismiss();
+                            show_alert(&strong, &alarms, &countdown, &pomodoro);
+                        }
+                        State::Btn(_) if pomodoro.ringing() => {
+                            pomodoro.dismiss();
+                            show_alert(&strong, &alarms, &countdown, &pomodoro);
+                        }
                         State::Btn(ref btn) => {
                             match btn {
-                                Btn::Right => {
-                                    strong.invoke_select_next();
-                                }
-                                Btn::Left => {
-                                    strong.invoke_select_prev();
-                                }
+                                Btn::Right => match show_clock {
+                                    true => {
+                                        strong.invoke_set_clock_face(Image::default());
+                                        faces.next(&mut frame).expect("switch watch face failed!");
+                                    }
+                                    false if strong.invoke_get_visible("alarms".into()) => {
+                                        alarm_index = (alarm_index + 1) % alarms.alarms().len().max(1);
+                                        show_alarms(&strong, &alarms, alarm_index);
+                                    }
+                                    false if strong.invoke_get_visible("stopwatch".into()) => {
+                                        stopwatch.lap_reset(Instant::now());
+                                        lap_page = 0;
+                                        show_laps(&strong, &stopwatch, lap_page);
+                                    }
+                                    false if strong.invoke_get_visible("timer".into()) => {
+                                        countdown.next();
+                                    }
+                                    false if strong.invoke_get_visible("pomodoro".into()) => {
+                                        pomodoro.skip();
+                                    }
+                                    false => {
+                                        strong.invoke_select_next();
+                                    }
+                                },
+                                Btn::Left => match show_clock {
+                                    true => {
+                                        strong.invoke_set_clock_face(Image::default());
+                                        faces.prev(&mut frame).expect("switch watch face failed!");
+                                    }
+                                    false if strong.invoke_get_visible("alarms".into()) => {
+                                        let count = alarms.alarms().len().max(1);
+                                        alarm_index = (alarm_index + count - 1) % count;
+                                        show_alarms(&strong, &alarms, alarm_index);
+                                    }
+                                    false if strong.invoke_get_visible("stopwatch".into()) => {
+                                        let pages = ((stopwatch.laps().count() + LAPS_SHOWN - 1) / LAPS_SHOWN).max(1);
+                                        lap_page = (lap_page + 1) % pages;
+                                        show_laps(&strong, &stopwatch, lap_page);
+                                    }
+                                    false if strong.invoke_get_visible("timer".into()) => {
+                                        countdown.prev();
+                                    }
+                                    false if strong.invoke_get_visible("pomodoro".into()) => {
+                                        pomodoro.reset();
+                                    }
+                                    false => {
+                                        strong.invoke_select_prev();
+                                    }
+                                },
                                 Btn::Exit => {
                                     show_clock = false;
+                                    show_world_clock = false;
+                                    strong.invoke_set_visible("clock".into(), false);
                                     strong.invoke_set_visible("info".into(), false);
+                                    strong.invoke_set_visible("world".into(), false);
+                                    strong.invoke_set_visible("alarms".into(), false);
+                                    strong.invoke_set_visible("stopwatch".into(), false);
+                                    strong.invoke_set_visible("timer".into(), false);
+                                    strong.invoke_set_visible("pomodoro".into(), false);
                                     strong.invoke_set_visible("about".into(), false);
                                     strong.invoke_set_visible("debug".into(), false);
                                     strong.invoke_set_visible("carousel".into(), true);
@@ -76,19 +280,66 @@ pub fn show_ui<D>(mut display: D, receiver: Receiver<State>) -> anyhow::Result<(
                                     match strong.invoke_selected() {
                                         //profile
                                         0 => {
-                                            show_info(strong.clone_strong());
+                                            info_page = match strong.invoke_get_visible("info".into()) {
+                                                true => (info_page + 1) % INFO_PAGES,
+                                                false => 0,
+                                            };
+                                            show_info(strong.clone_strong(), info_page);
                                         }
                                         //home
                                         1 => {
                                             show_clock = true;
-                                            clock_update_interval = 0;
+                                            strong.invoke_set_clock_face(Image::default());
+                                            faces.reset(&mut frame).expect("show clock failed!");
+                                            strong.invoke_set_visible("clock".into(), true);
                                         }
-                                        //debug
+                                        //world
                                         2 => {
+                                            show_world_clock = true;
+                                            show_world(&strong);
+                                            strong.invoke_set_visible("world".into(), true);
+                                        }
+                                        //alarm, Ok switches the selected one on or off
+                                        3 => {
+                                            match strong.invoke_get_visible("alarms".into()) {
+                                                true => alarms.toggle(alarm_index),
+                                                false => alarm_index = 0,
+                                            }
+                                            show_alarms(&strong, &alarms, alarm_index);
+                                            strong.invoke_set_visible("alarms".into(), true);
+                                        }
+                                        //stopwatch, keeps running behind the other pages
+                                        4 => {
+                                            match strong.invoke_get_visible("stopwatch".into()) {
+                                                true => stopwatch.start_stop(Instant::now()),
+                                                false => lap_page = 0,
+                                            }
+                                            page_shown.clear();
+                                            show_laps(&strong, &stopwatch, lap_page);
+                                            strong.invoke_set_visible("stopwatch".into(), true);
+                                        }
+                                        //timer, same
+                                        5 => {
+                                            if strong.invoke_get_visible("timer".into()) {
+                                                countdown.start_pause(Instant::now());
+                                            }
+                                            page_shown.clear();
+                                            strong.invoke_set_visible("timer".into(), true);
+                                        }
+                                        //pomodoro, same
+                                        6 => {
+                                            if strong.invoke_get_visible("pomodoro".into()) {
+                                                pomodoro.start_pause(Instant::now());
+                                            }
+                                            page_shown.clear();
+                                            strong.invoke_set_visible("pomodoro".into(), true);
+                                        }
+                                        //debug
+                                        7 => {
                                             strong.invoke_set_visible("debug".into(), true);
                                         }
                                         //about
-                                        3 => {
+                                        8 => {
                                             strong.invoke_set_visible("about".into(), true);
                                         }
                                         _ => unreachable!()
@@ -100,18 +351,84 @@ pub fn show_ui<D>(mut display: D, receiver: Receiver<State>) -> anyhow::Result<(
                         _ => {}
                     }
                     if strong.invoke_get_visible("debug".into()) {
-                        strong.invoke_set_debug_text(format!("{}", state).into(), Color::from_rgb_u8(255, 255, 255), 24, 400);
+                        strong.invoke_set_debug_text(format!("{}\n{}", tz::format(&Utc::now(), "%H:%M:%S %Z"), state).into(), Color::from_rgb_u8(255, 255, 255), 24, 400);
                         unsafe {heap_caps_print_heap_info(MALLOC_CAP_DEFAULT)}
                     }
 
                 }
                 Err(_) => {}
             }
-            if show_clock && clock_update_interval >= 800 {
-                clock.update(&mut display).expect("show clock failed!");
-                clock_update_interval = 0;
+            let now = Utc::now();
+            if now.timestamp() != alarm_polled {
+                alarm_polled = now.timestamp();
+                if alarms.poll(&now, &tz::LOCAL).is_some() {
+                    show_alert(&strong, &alarms, &countdown, &pomodoro);
+                    // a one-off alarm has just switched itself off
+                    if strong.invoke_get_visible("alarms".into()) {
+                        show_alarms(&strong, &alarms, alarm_index);
+                    }
+                }
+                // an alert already has the buzzer
+                if let Some(ringtone) = chimes.poll(&tz::local(&now)) {
+                    if !strong.invoke_get_visible("alert".into()) {
+                        sound::play(&ringtone, false);
+                    }
+                }
+            }
+            let instant = Instant::now();
+            if countdown.poll(instant) {
+                show_alert(&strong, &alarms, &countdown, &pomodoro);
+            }
+            if pomodoro.poll(instant, tz::local(&now).date_naive()) {
+                show_alert(&strong, &alarms, &countdown, &pomodoro);
+            }
+            if strong.invoke_get_visible("stopwatch".into()) {
+                let time = format_duration(stopwatch.elapsed(instant), true);
+                if time != page_shown {
+                    strong.invoke_set_stopwatch(time.as_str().into(), stopwatch.running());
+                    page_shown = time;
+                }
+            }
+            if strong.invoke_get_visible("timer".into()) {
+                // rounded up, it reads 0:00 only once it has run out
+                let time = format_duration(countdown.left(instant) + Duration::from_millis(999), false);
+                let status = match (countdown.idle(), countdown.running()) {
+                    (true, _) => "OK start  <> time",
+                    (false, true) => "OK pause  > stop",
+                    (false, false) => "OK go on  > stop",
+                };
+                let shown = format!("{} {}", time, status);
+                if shown != page_shown {
+                    strong.invoke_set_timer(time.into(), status.into(), countdown.progress(instant), countdown.running());
+                    page_shown = shown;
+                }
+            }
+            if strong.invoke_get_visible("pomodoro".into()) {
+                let time = format_duration(pomodoro.left(instant) + Duration::from_millis(999), false);
+                let today = tz::local(&now).date_naive();
+                let sessions = format!("today {}  week {}", pomodoro.sessions(today), pomodoro.week(today));
+                let status = match (pomodoro.ready(), pomodoro.running()) {
+                    (true, _) => "OK start  > skip  < reset",
+                    (false, true) => "OK pause  > skip",
+                    (false, false) => "OK go on  > skip",
+                };
+                let shown = format!("{} {} {} {}", pomodoro.phase().name(), time, sessions, status);
+                if shown != page_shown {
+                    strong.invoke_set_pomodoro(pomodoro.phase().name().into(), time.into(), sessions.into(), status.into(),
+                                               pomodoro.progress(instant), pomodoro.running(), pomodoro.phase() == Phase::Work);
+                    page_shown = shown;
+                }
+            }
+            if show_clock && faces.due() {
+                // take the image back from the scene so the frame is drawn in place rather than copied
+                strong.invoke_set_clock_face(Image::default());
+                faces.update(&mut frame).expect("show clock failed!");
+                strong.invoke_set_clock_face(frame.image());
+            }
+            if show_world_clock && now.timestamp() != world_shown {
+                show_world(&strong);
+                world_shown = now.timestamp();
             }
-            clock_update_interval += 10;
         },
     );
     root.run().unwrap();
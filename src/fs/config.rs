@@ -1,20 +1,226 @@
 use lazy_static::lazy_static;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::alarm::Alarm;
 use crate::fs::DATA_PART;
 
+const CONFIG_MAX_SIZE: usize = 4096;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Wifi {
     pub ssid: String,
     pub password: String,
 }
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HourFormat {
+    #[serde(rename = "12h")]
+    H12,
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+}
+impl HourFormat {
+    /// strftime pattern of the hour field
+    pub fn hour(&self) -> &'static str {
+        match self {
+            HourFormat::H12 => "%I",
+            HourFormat::H24 => "%H",
+        }
+    }
+    /// `hh:mm:ss`, with the AM/PM marker in 12h mode
+    pub fn time(&self) -> &'static str {
+        match self {
+            HourFormat::H12 => "%I:%M:%S %p",
+            HourFormat::H24 => "%H:%M:%S",
+        }
+    }
+    /// `hh:mm`, with the AM/PM marker in 12h mode
+    pub fn hour_minute(&self) -> &'static str {
+        match self {
+            HourFormat::H12 => "%I:%M %p",
+            HourFormat::H24 => "%H:%M",
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorldClock {
+    pub name: String,
+    /// IANA name or POSIX TZ string, same as `time_zone`
+    pub time_zone: String,
+}
+/// lengths in minutes of the pomodoro phases
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct PomodoroIntervals {
+    pub work: u32,
+    pub short_break: u32,
+    pub long_break: u32,
+    /// work sessions before a long break
+    pub long_break_after: u32,
+}
+impl Default for PomodoroIntervals {
+    fn default() -> Self {
+        Self { work: 25, short_break: 5, long_break: 15, long_break_after: 4 }
+    }
+}
+/// piezo buzzer on a PWM pin, the sounds are RTTTL ringtones
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Buzzer {
+    /// GPIO the buzzer is wired to, without one the clock stays silent
+    pub pin: Option<i32>,
+    /// PWM duty in percent, 50 is the loudest
+    pub volume: u32,
+    pub alarm: String,
+    pub timer: String,
+    pub pomodoro: String,
+}
+impl Default for Buzzer {
+    fn default() -> Self {
+        Self {
+            pin: None,
+            volume: 50,
+            alarm: "alarm:d=16,o=6,b=140:c7,p,c7,p,c7,p,c7,4p".into(),
+            timer: "timer:d=8,o=6,b=160:e,p,e,p,e".into(),
+            pomodoro: "pomodoro:d=8,o=5,b=160:c6,e6,g6,4c7".into(),
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChimeSound {
+    #[default]
+    Off,
+    Beep,
+    /// one strike for each hour on a 12 hour dial
+    Strikes,
+    Melody,
+}
+/// chimes on the buzzer on the hour and, if wanted, one beep or strike on the half hour
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Chime {
+    pub sound: ChimeSound,
+    pub half_hour: bool,
+    /// hours from `quiet_from` up to `quiet_until` stay silent, the same hour in both keeps none
+    pub quiet_from: u32,
+    pub quiet_until: u32,
+    /// RTTTL ringtone played on the hour with `"melody"`
+    pub melody: String,
+}
+impl Default for Chime {
+    fn default() -> Self {
+        Self {
+            sound: ChimeSound::Off,
+            half_hour: false,
+            quiet_from: 22,
+            quiet_until: 7,
+            melody: "westminster:d=4,o=5,b=100:e,g#,f#,2b4,e,f#,g#,2e".into(),
+        }
+    }
+}
+/// gps receiver on UART1, its NMEA sentences are read as a time source
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Gps {
+    pub tx: i32,
+    pub rx: i32,
+    #[serde(default = "default_gps_baud")]
+    pub baud: u32,
+}
+/// ds3231 real time clock on I2C0, kept in sync and read when nothing else answers
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Rtc {
+    pub sda: i32,
+    pub scl: i32,
+    #[serde(default = "default_rtc_address")]
+    pub address: u8,
+}
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     pub wifi: Vec<Wifi>,
     pub sync_time_interval: u32,
     pub date_fixed_offset: i32,
-    pub ntp_server: String
+    /// a single server name is still accepted for older config files
+    #[serde(alias = "ntp_server", deserialize_with = "one_or_many")]
+    pub ntp_servers: Vec<String>,
+    /// milliseconds to wait for each ntp answer
+    #[serde(default = "default_ntp_timeout")]
+    pub ntp_timeout: u32,
+    /// extra attempts per server before giving up on it
+    #[serde(default = "default_ntp_retries")]
+    pub ntp_retries: u32,
+    /// upper bound in seconds of the poll interval while syncs keep failing
+    #[serde(default = "default_ntp_max_backoff")]
+    pub ntp_max_backoff: u32,
+    /// answer sntp requests on udp 123 once the clock is synced
+    #[serde(default)]
+    pub sntp_server: bool,
+    /// HEAD requests whose `Date` header is used when ntp gets no answer
+    #[serde(default = "default_http_time_urls")]
+    pub http_time_urls: Vec<String>,
+    /// rfc 3339 time to start from when no better source answers
+    #[serde(default)]
+    pub manual_time: Option<String>,
+    /// IANA name such as `Europe/Berlin` or a POSIX TZ string, overrides `date_fixed_offset`
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// cities listed on the world clock page
+    #[serde(default)]
+    pub world_clocks: Vec<WorldClock>,
+    /// `"12h"` or `"24h"`
+    #[serde(default)]
+    pub hour_format: HourFormat,
+    /// strftime pattern of the date under the clock face, `"%a %d %b"` gives `Mon 18 Oct`
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// alternate the date under the clock face with the lunar date and solar term
+    #[serde(default)]
+    pub lunar_calendar: bool,
+    /// move the second hand of the analog face smoothly instead of once a second
+    #[serde(default)]
+    pub sweep_second_hand: bool,
+    /// frames per second of the sweeping hand, lowered on its own while frames take too long
+    #[serde(default = "default_sweep_fps")]
+    pub sweep_fps: u32,
+    /// alarms set up on the first start, after that the list kept in flash is used
+    #[serde(default)]
+    pub alarms: Vec<Alarm>,
+    #[serde(default)]
+    pub pomodoro: PomodoroIntervals,
+    #[serde(default)]
+    pub buzzer: Buzzer,
+    #[serde(default)]
+    pub chime: Chime,
+    #[serde(default)]
+    pub gps: Option<Gps>,
+    #[serde(default)]
+    pub rtc: Option<Rtc>,
+}
+
+const fn default_ntp_timeout() -> u32 { 2000 }
+const fn default_ntp_retries() -> u32 { 2 }
+const fn default_ntp_max_backoff() -> u32 { 3600 }
+const fn default_sweep_fps() -> u32 { 10 }
+const fn default_gps_baud() -> u32 { 9600 }
+const fn default_rtc_address() -> u8 { 0x68 }
+fn default_date_format() -> String { "%Y-%m-%d".into() }
+fn default_http_time_urls() -> Vec<String> {
+    vec!["http://www.baidu.com".into(), "http://www.google.com".into()]
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
 }
+
 impl Config{
     pub fn from_partition() -> Option<Config>{
         let partition = *DATA_PART;
@@ -23,7 +229,7 @@ impl Config{
                 None
             },
             Some(part) => {
-                let mut buffer = vec![0u8; 512];
+                let mut buffer = vec![0u8; CONFIG_MAX_SIZE];
                 part.read(0, &mut buffer).ok()?;
                 let (end_idx, _) = buffer.iter().enumerate().find(|(_i, v)|**v == 0xff)?;
                 let json_str = std::str::from_utf8(buffer.split_at(end_idx).0).ok()?;
@@ -40,4 +246,4 @@ impl Config{
 
 lazy_static!{
     pub static ref CONFIG: Option<Config> = Config::from_partition();
-}
\ No newline at end of file
+}
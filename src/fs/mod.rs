@@ -10,6 +10,7 @@ use lazy_static::lazy_static;
 use log::{error, info};
 
 pub mod config;
+pub mod store;
 
 #[repr(transparent)]
 #[derive(Copy, Clone, Debug)]
@@ -0,0 +1,43 @@
+use std::sync::Mutex;
+
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+use lazy_static::lazy_static;
+use log::error;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// runtime state lives in nvs, `config.json` on the data partition stays read only
+const NAMESPACE: &str = "clock";
+
+lazy_static!{
+    static ref STORE: Mutex<Option<EspNvs<NvsDefault>>> = Mutex::new(None);
+}
+
+pub fn setup_store(nvs: EspDefaultNvsPartition) -> anyhow::Result<()> {
+    let store = EspNvs::new(nvs, NAMESPACE, true)?;
+    STORE.lock().unwrap().replace(store);
+    Ok(())
+}
+
+/// `key` is limited to 15 characters by nvs
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let store = STORE.lock().unwrap();
+    let store = store.as_ref()?;
+    let len = store.blob_len(key).ok()??;
+    let mut buffer = vec![0u8; len];
+    let data = store.get_raw(key, &mut buffer).ok()??;
+    match serde_json::from_slice(data) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            error!("stored {} is invalid! {}", key, e);
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) -> anyhow::Result<()> {
+    let mut store = STORE.lock().unwrap();
+    let store = store.as_mut().ok_or(anyhow::Error::msg("store not initialized"))?;
+    store.set_raw(key, &serde_json::to_vec(value)?)?;
+    Ok(())
+}
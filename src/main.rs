@@ -1,9 +1,10 @@
 use std::thread;
 use anyhow::Result;
+use clock_core::calendar;
 use esp_idf_hal::peripherals::Peripherals;
 use esp_idf_svc::eventloop::EspSystemEventLoop;
 use esp_idf_svc::nvs::EspDefaultNvsPartition;
-use log::{info, LevelFilter};
+use log::{error, info, LevelFilter};
 
 use display::setup_display;
 use net::setup_network;
@@ -17,6 +18,10 @@ mod net;
 mod fs;
 pub mod button;
 mod utils;
+mod tz;
+mod alarm;
+mod timer;
+mod sound;
 
 pub const DISPLAY_WIDTH: u32 = 128;
 pub const DISPLAY_HEIGHT: u32 = 128;
@@ -49,8 +54,12 @@ fn main() -> Result<()> {
     let modem = per.modem;
     let sys_loop = EspSystemEventLoop::take()?;
     let nvs = EspDefaultNvsPartition::take()?;
+    fs::store::setup_store(nvs.clone())?;
+    if let Err(e) = sound::setup_sound(per.ledc.timer0, per.ledc.channel0) {
+        error!("setup buzzer failed! {}", e);
+    }
     info!("setup network!");
-    setup_network(modem, sys_loop, nvs)?;
+    setup_network(modem, sys_loop, nvs, per.uart1, per.i2c0)?;
     show_ui(display, state_receiver).unwrap();
     Ok(())
 }
@@ -0,0 +1,32 @@
+use std::time::Duration as StdDuration;
+
+use embedded_svc::http::client::Client;
+use embedded_svc::http::{Headers, Method};
+use esp_idf_svc::http::client::{Configuration, EspHttpConnection};
+
+use crate::net::http_date::HeadRequest;
+
+/// HEAD requests through the esp-idf http client, https urls are checked against the certificate bundle
+pub struct EspHead {
+    timeout: StdDuration,
+}
+
+impl EspHead {
+    pub fn new(timeout: StdDuration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl HeadRequest for EspHead {
+    fn date(&mut self, url: &str) -> anyhow::Result<Option<String>> {
+        let connection = EspHttpConnection::new(&Configuration {
+            timeout: Some(self.timeout),
+            crt_bundle_attach: Some(esp_idf_svc::sys::esp_crt_bundle_attach),
+            ..Default::default()
+        })?;
+        let mut client = Client::wrap(connection);
+        let response = client.request(Method::Head, url, &[])?.submit()?;
+        Ok(response.header("Date").map(String::from))
+    }
+}
+
@@ -1,22 +1,47 @@
 use std::ffi::c_long;
 use std::ops::Deref;
+use std::sync::Mutex;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow;
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
 use embedded_svc::ipv4::IpInfo;
+use esp_idf_hal::gpio::AnyIOPin;
+use esp_idf_hal::i2c::{I2cConfig, I2cDriver, I2C0};
 use esp_idf_hal::modem::Modem;
+use esp_idf_hal::prelude::*;
+use esp_idf_hal::uart::{config::Config as UartConfig, UartDriver, UART1};
 use esp_idf_svc::eventloop::EspEventLoop;
 use esp_idf_svc::eventloop::System;
 use esp_idf_svc::nvs::{EspNvsPartition, NvsDefault};
-use esp_idf_svc::sys::{clock_settime, clockid_t, time_t, timespec};
+use esp_idf_svc::sys::{adjtime, clock_settime, clockid_t, time_t, timespec, timeval};
 use esp_idf_svc::wifi::{AuthMethod, BlockingWifi, ClientConfiguration, Configuration, EspWifi};
+use lazy_static::lazy_static;
 use log::{error, info};
 
-use crate::fs::config::CONFIG;
-use crate::net::ntp::NtpClient;
+use crate::fs::config::{Gps, Rtc, CONFIG};
+use crate::fs::store;
+use crate::net::drift::{DriftEstimator, DriftState};
+use crate::net::gps::GpsSource;
+use crate::net::esp_head::EspHead;
+use crate::net::http_date::HttpSource;
+use crate::net::ntp::{Backoff, Leap, NTP_PORT, NtpClient, NtpTimestamp};
+use crate::net::peer::Peers;
+use crate::net::rtc::RtcSource;
+use crate::net::server::{NtpServer, Reference};
+use crate::net::source::{ManualSource, NtpSource, Scheduler};
+use crate::net::status::SyncStatus;
+use crate::net::uart::UartReader;
+use crate::tz;
+use crate::utils::pins;
+
+pub mod esp_head;
+pub mod status;
+pub mod uart;
+
+pub use clock_core::net::{drift, gps, http_date, ntp, peer, rtc, select, server, source};
 
-pub mod ntp;
 static mut NET_INFO: Option<IpInfo> = None;
 pub fn net_info() -> Option<IpInfo> {
     unsafe {NET_INFO}
@@ -68,6 +93,8 @@ pub fn setup_network(
     modem: Modem,
     sys_loop: EspEventLoop<System>,
     nvs: EspNvsPartition<NvsDefault>,
+    uart: UART1,
+    i2c: I2C0,
 ) -> anyhow::Result<()> {
     let wifi = setup_wifi(modem, sys_loop.clone(), nvs.clone()).expect("setup_wifi failed");
     info!("{:?}", wifi.wifi().sta_netif().get_ip_info().unwrap());
@@ -76,50 +103,238 @@ pub fn setup_network(
         .stack_size(1024 * 16)
         .name("ntp-update".into())
         .spawn(move || {
-            match sync_time() {
-                Ok(_) => info!("ntp sync time succeed!"),
-                Err(e) => error!("ntp sync time failed! {}", e),
-            }
-            let mut interval = 0u32;
-            let sync_time_interval = match CONFIG.deref() {
-                None => 3600,
-                Some(config) => config.sync_time_interval,
+            let mut backoff = match CONFIG.deref() {
+                None => Backoff::new(3600, NTP_MIN_BACKOFF, 3600),
+                Some(config) => Backoff::new(config.sync_time_interval, NTP_MIN_BACKOFF, config.ntp_max_backoff),
             };
-
+            let mut scheduler = time_sources(uart, i2c);
+            let mut drift = DriftEstimator::new(store::load::<DriftState>(DRIFT_KEY).unwrap_or_default());
+            STATUS.lock().unwrap().drift_ppm = Some(drift.ppm());
             loop {
-                if interval >= sync_time_interval {
-                    match sync_time() {
-                        Ok(_) => info!("ntp sync time succeed!"),
-                        Err(e) => error!("ntp sync time failed! {}", e),
+                let interval = match sync_time(&mut scheduler, &mut drift) {
+                    Ok(_) => {
+                        info!("sync time succeed!");
+                        backoff.success()
                     }
-                    interval = 0
-                }
-                thread::sleep(Duration::from_secs(1));
-                interval += 1;
+                    Err(e) => {
+                        error!("sync time failed! {}", e);
+                        STATUS.lock().unwrap().failure(e.to_string());
+                        backoff.failure()
+                    }
+                };
+                STATUS.lock().unwrap().schedule(Utc::now() + chrono::Duration::seconds(interval as i64));
+                wait_next_sync(Duration::from_secs(interval as u64), &drift);
             }
         })
         .unwrap();
+    if CONFIG.as_ref().is_some_and(|config| config.sntp_server) {
+        thread::Builder::new()
+            .stack_size(1024 * 8)
+            .name("sntp-server".into())
+            .spawn(|| match NtpServer::bind(("0.0.0.0", NTP_PORT)) {
+                Ok(server) => server.serve(reference),
+                Err(e) => error!("sntp server bind failed! {}", e),
+            })
+            .unwrap();
+    }
     Ok(())
 }
 
 
-const CLOCK_MONOTONIC: clockid_t = 1;
-pub fn sync_time() -> anyhow::Result<()> {
-    let ntp_server = match CONFIG.deref() {
-        None => "ntp0.ntp-servers.net",
-        Some(config) => &*config.ntp_server,
+const CLOCK_REALTIME: clockid_t = 1;
+lazy_static!{
+    static ref STATUS: Mutex<SyncStatus> = Mutex::new(SyncStatus::default());
+    static ref REFERENCE: Mutex<Option<Reference>> = Mutex::new(None);
+    static ref LEAP: Mutex<Leap> = Mutex::new(Leap::None);
+}
+/// snapshot of the time sync state
+pub fn sync_status() -> SyncStatus {
+    STATUS.lock().unwrap().clone()
+}
+
+/// state announced by the sntp server, `None` until the first successful sync
+pub fn reference() -> Option<Reference> {
+    *REFERENCE.lock().unwrap()
+}
+
+/// leap second announced by the last successful sync
+pub fn leap() -> Leap {
+    *LEAP.lock().unwrap()
+}
+
+/// first second of next month, where an announced leap second takes effect
+fn leap_boundary(now: &DateTime<Utc>) -> DateTime<Utc> {
+    let (year, month) = match now.month() {
+        12 => (now.year() + 1, 1),
+        m => (now.year(), m + 1),
     };
-    let client = NtpClient::new();
-    let res = client.request(ntp_server)?;
-    let seconds = res.unix_time.clone();
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap()
+}
+
+/// the system clock has no notion of leap seconds, an inserted one shows up as an extra
+/// 00:00:00 until the clock is stepped back, that second should read 23:59:60
+pub fn in_leap_second(utc: &DateTime<Utc>) -> bool {
+    leap() == Leap::Insert && utc.day() == 1 && utc.num_seconds_from_midnight() == 0
+}
+
+fn step_clock(offset: chrono::Duration) -> DateTime<Utc> {
+    let now = DateTime::<Utc>::from(SystemTime::now()) + offset;
     let t = timespec {
-        tv_sec: seconds as time_t,
-        tv_nsec: (seconds / 1000000000) as c_long,
+        tv_sec: now.timestamp() as time_t,
+        tv_nsec: (now.timestamp_subsec_millis() * 1_000_000) as c_long,
     };
     unsafe {
-        clock_settime(CLOCK_MONOTONIC, &t as *const timespec);
+        clock_settime(CLOCK_REALTIME, &t as *const timespec);
+    }
+    now
+}
+
+/// gradually moves the clock by `offset` instead of jumping
+fn slew_clock(offset: chrono::Duration) {
+    let micros = offset.num_microseconds().unwrap_or(0);
+    let delta = timeval {
+        tv_sec: (micros / 1_000_000) as time_t,
+        tv_usec: (micros % 1_000_000) as _,
+    };
+    unsafe {
+        adjtime(&delta as *const timeval, std::ptr::null_mut());
     }
-    info!("sync time: {}", res.format_time("%Y-%m-%d %H:%M:%S")?);
-    Ok(())
 }
 
+/// sleeps until the next sync, applying the drift correction every `DRIFT_PERIOD`
+/// and stepping the clock at an announced leap second on the way
+fn wait_next_sync(interval: Duration, drift: &DriftEstimator) {
+    let deadline = Instant::now() + interval;
+    let mut last = Instant::now();
+    let now = DateTime::<Utc>::from(SystemTime::now());
+    let boundary = leap_boundary(&now);
+    let mut leap_step = match leap() {
+        Leap::Insert => Some((boundary + chrono::Duration::seconds(1), chrono::Duration::seconds(-1))),
+        Leap::Delete => Some((boundary - chrono::Duration::seconds(1), chrono::Duration::seconds(1))),
+        _ => None,
+    };
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        let mut sleep = (deadline - now).min(DRIFT_PERIOD);
+        if let Some((at, _)) = leap_step {
+            let until = (at - DateTime::<Utc>::from(SystemTime::now())).to_std().unwrap_or_default();
+            sleep = sleep.min(until);
+        }
+        thread::sleep(sleep);
+        if let Some((at, step)) = leap_step {
+            if DateTime::<Utc>::from(SystemTime::now()) >= at {
+                step_clock(step);
+                leap_step = None;
+                *LEAP.lock().unwrap() = Leap::None;
+                if let Some(reference) = REFERENCE.lock().unwrap().as_mut() {
+                    reference.leap = Leap::None;
+                }
+                info!("leap second applied");
+            }
+        }
+        slew_clock(drift.correction(last.elapsed()));
+        last = Instant::now();
+    }
+}
+
+const DEFAULT_NTP_SERVERS: [&str; 2] = ["ntp0.ntp-servers.net", "pool.ntp.org"];
+/// first retry delay in seconds after a failed sync
+const NTP_MIN_BACKOFF: u32 = 16;
+const HTTP_TIMEOUT: u64 = 5000;
+/// how often the drift correction is applied between syncs
+const DRIFT_PERIOD: Duration = Duration::from_secs(60);
+const DRIFT_KEY: &str = "drift";
+/// how far a hand entered time may be off
+const MANUAL_ACCURACY: i64 = 30;
+
+fn gps_source(uart: UART1, gps: &Gps) -> anyhow::Result<GpsSource<UartReader<'static>>> {
+    let config = UartConfig::new().baudrate(Hertz(gps.baud));
+    let driver = UartDriver::new(
+        uart,
+        pins::claim(gps.tx)?,
+        pins::claim(gps.rx)?,
+        Option::<AnyIOPin>::None,
+        Option::<AnyIOPin>::None,
+        &config,
+    )?;
+    Ok(GpsSource::new(UartReader::new(driver)))
+}
+
+fn rtc_source(i2c: I2C0, rtc: &Rtc) -> anyhow::Result<RtcSource<I2cDriver<'static>>> {
+    let config = I2cConfig::new().baudrate(100.kHz().into());
+    let driver = I2cDriver::new(i2c, pins::claim(rtc.sda)?, pins::claim(rtc.scl)?, &config)?;
+    Ok(RtcSource::new(driver, rtc.address))
+}
+
+fn time_sources(uart: UART1, i2c: I2C0) -> Scheduler {
+    let mut scheduler = Scheduler::new();
+    let (peers, client) = match CONFIG.deref() {
+        Some(config) if !config.ntp_servers.is_empty() => (
+            Peers::new(&config.ntp_servers),
+            NtpClient::new(Duration::from_millis(config.ntp_timeout as u64), config.ntp_retries),
+        ),
+        _ => (Peers::new(&DEFAULT_NTP_SERVERS), NtpClient::default()),
+    };
+    scheduler.add(Box::new(NtpSource::new(peers, client)));
+    if let Some(config) = CONFIG.deref() {
+        let head = EspHead::new(Duration::from_millis(HTTP_TIMEOUT));
+        scheduler.add(Box::new(HttpSource::new(&config.http_time_urls, head)));
+    }
+    if let Some(manual_time) = CONFIG.as_ref().and_then(|config| config.manual_time.as_ref()) {
+        match DateTime::parse_from_rfc3339(manual_time) {
+            Ok(time) => {
+                let mut manual = ManualSource::new(chrono::Duration::seconds(MANUAL_ACCURACY));
+                manual.enter(time.with_timezone(&Utc));
+                scheduler.add(Box::new(manual));
+            }
+            Err(e) => error!("manual time {} invalid! {}", manual_time, e),
+        }
+    }
+    if let Some(gps) = CONFIG.as_ref().and_then(|config| config.gps.as_ref()) {
+        match gps_source(uart, gps) {
+            Ok(source) => scheduler.add(Box::new(source)),
+            Err(e) => error!("setup gps failed! {}", e),
+        }
+    }
+    if let Some(rtc) = CONFIG.as_ref().and_then(|config| config.rtc.as_ref()) {
+        match rtc_source(i2c, rtc) {
+            Ok(source) => scheduler.add(Box::new(source)),
+            Err(e) => error!("setup rtc failed! {}", e),
+        }
+    }
+    scheduler
+}
+
+pub fn sync_time(scheduler: &mut Scheduler, drift: &mut DriftEstimator) -> anyhow::Result<()> {
+    let sample = scheduler.sample()?;
+    let now = match DriftEstimator::should_slew(&sample.offset) {
+        true => {
+            slew_clock(sample.offset);
+            if drift.add(DateTime::<Utc>::from(SystemTime::now()).timestamp() as f64, &sample.offset) {
+                if let Err(e) = store::save(DRIFT_KEY, &drift.state()) {
+                    error!("save drift failed: {}", e);
+                }
+            }
+            DateTime::<Utc>::from(SystemTime::now()) + sample.offset
+        }
+        false => {
+            drift.reset();
+            step_clock(sample.offset)
+        }
+    };
+    scheduler.set_time(&sample, &now);
+    {
+        let mut status = STATUS.lock().unwrap();
+        status.success(&sample, now);
+        status.drift_ppm = Some(drift.ppm());
+    }
+    *LEAP.lock().unwrap() = sample.leap;
+    REFERENCE.lock().unwrap().replace(Reference::new(&sample, NtpTimestamp::now()));
+    info!("sync time: {} from {}, offset: {}ms, accuracy: {}ms, drift: {:.2}ppm, leap: {:?}",
+        tz::format(&now, "%Y-%m-%d %H:%M:%S%.3f %Z"), sample.source, sample.offset.num_milliseconds(),
+        sample.accuracy.num_milliseconds(), drift.ppm(), sample.leap);
+    Ok(())
+}
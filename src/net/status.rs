@@ -0,0 +1,55 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::net::source::TimeSample;
+
+/// sync results kept for the profile page
+const HISTORY: usize = 5;
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncStatus {
+    pub last_success: Option<DateTime<Utc>>,
+    pub source: Option<String>,
+    pub offset: Option<Duration>,
+    pub delay: Option<Duration>,
+    pub drift_ppm: Option<f64>,
+    pub failures: u32,
+    pub last_error: Option<String>,
+    pub next_sync: Option<DateTime<Utc>>,
+    /// offsets of the last syncs, oldest first, `None` for a failed one
+    pub history: VecDeque<Option<Duration>>,
+}
+
+impl SyncStatus {
+    fn push(&mut self, offset: Option<Duration>) {
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(offset);
+    }
+    pub fn success(&mut self, sample: &TimeSample, now: DateTime<Utc>) {
+        self.last_success = Some(now);
+        self.source = Some(sample.source.clone());
+        self.offset = Some(sample.offset);
+        self.delay = Some(sample.delay);
+        self.failures = 0;
+        self.last_error = None;
+        self.push(Some(sample.offset));
+    }
+    pub fn failure(&mut self, error: String) {
+        self.failures += 1;
+        self.last_error = Some(error);
+        self.push(None);
+    }
+    pub fn schedule(&mut self, next: DateTime<Utc>) {
+        self.next_sync = Some(next);
+    }
+    /// never synced, or the last success is older than `max_age`
+    pub fn is_stale(&self, now: &DateTime<Utc>, max_age: Duration) -> bool {
+        match self.last_success {
+            None => true,
+            Some(last) => *now - last > max_age,
+        }
+    }
+}
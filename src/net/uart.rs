@@ -0,0 +1,36 @@
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+
+use esp_idf_hal::delay::TickType;
+use esp_idf_hal::uart::UartDriver;
+
+/// the gps source reads whole lines, a read waiting longer than this ends the sample
+const READ_TIMEOUT_MS: u64 = 1500;
+/// bytes older than this were queued while nobody listened and describe a time long gone
+const STALE: Duration = Duration::from_millis(200);
+
+/// an uart as a byte stream, a read of 0 bytes means nothing arrived in time
+pub struct UartReader<'d> {
+    driver: UartDriver<'d>,
+    last_read: Option<Instant>,
+}
+
+impl<'d> UartReader<'d> {
+    pub fn new(driver: UartDriver<'d>) -> Self {
+        Self { driver, last_read: None }
+    }
+}
+
+impl Read for UartReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.last_read.map_or(true, |last| last.elapsed() > STALE) {
+            self.driver.clear_rx().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        }
+        let read = self
+            .driver
+            .read(buf, TickType::new_millis(READ_TIMEOUT_MS).ticks())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        self.last_read = Some(Instant::now());
+        Ok(read)
+    }
+}
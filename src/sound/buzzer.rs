@@ -0,0 +1,40 @@
+use esp_idf_hal::ledc::{CHANNEL0, LedcDriver, LedcTimerDriver, Resolution, TIMER0};
+use esp_idf_hal::ledc::config::TimerConfig;
+use esp_idf_hal::prelude::FromValueType;
+use esp_idf_hal::sys::{esp, ledc_mode_t_LEDC_LOW_SPEED_MODE, ledc_set_freq, ledc_timer_t_LEDC_TIMER_0};
+
+use crate::sound::player::ToneOutput;
+use crate::utils::pins;
+
+/// passive piezo on LEDC timer 0 and channel 0, the timer follows the note and the duty sets the volume
+pub struct LedcBuzzer<'d> {
+    driver: LedcDriver<'d>,
+    /// the channel only borrows it for setup, dropping it would stop the timer
+    _timer: LedcTimerDriver<'d>,
+    duty: u32,
+}
+
+impl LedcBuzzer<'static> {
+    /// `volume` is the duty in percent, a square wave at 50 is the loudest
+    pub fn new(timer: TIMER0, channel: CHANNEL0, pin: i32, volume: u32) -> anyhow::Result<Self> {
+        let config = TimerConfig::new().frequency(1.kHz().into()).resolution(Resolution::Bits10);
+        let timer = LedcTimerDriver::new(timer, &config)?;
+        let mut driver = LedcDriver::new(channel, &timer, pins::claim(pin)?)?;
+        driver.set_duty(0)?;
+        let duty = driver.get_max_duty() * volume.min(50) / 100;
+        Ok(Self { driver, _timer: timer, duty })
+    }
+}
+
+impl ToneOutput for LedcBuzzer<'_> {
+    fn tone(&mut self, frequency: Option<u32>) -> anyhow::Result<()> {
+        match frequency {
+            Some(frequency) => {
+                esp!(unsafe { ledc_set_freq(ledc_mode_t_LEDC_LOW_SPEED_MODE, ledc_timer_t_LEDC_TIMER_0, frequency) })?;
+                self.driver.set_duty(self.duty)?;
+            }
+            None => self.driver.set_duty(0)?,
+        }
+        Ok(())
+    }
+}
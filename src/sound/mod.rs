@@ -0,0 +1,74 @@
+use std::sync::Mutex;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Instant;
+
+use esp_idf_hal::ledc::{CHANNEL0, TIMER0};
+use lazy_static::lazy_static;
+use log::error;
+
+use crate::fs::config::CONFIG;
+use crate::sound::buzzer::LedcBuzzer;
+use crate::sound::player::Player;
+use crate::sound::rtttl::Note;
+
+pub mod buzzer;
+
+pub use clock_core::sound::{player, rtttl};
+
+enum Sound {
+    Play(Vec<Note>, bool),
+    Stop,
+}
+
+lazy_static!{
+    static ref SOUND: Mutex<Option<Sender<Sound>>> = Mutex::new(None);
+}
+
+/// starts the thread playing on the buzzer, without a buzzer pin in the config the clock stays silent
+pub fn setup_sound(timer: TIMER0, channel: CHANNEL0) -> anyhow::Result<()> {
+    let (pin, volume) = match CONFIG.as_ref().and_then(|config| Some((config.buzzer.pin?, config.buzzer.volume))) {
+        Some(buzzer) => buzzer,
+        None => return Ok(()),
+    };
+    let buzzer = LedcBuzzer::new(timer, channel, pin, volume)?;
+    let (sender, receiver) = channel::<Sound>();
+    thread::Builder::new()
+        .stack_size(4096)
+        .name(String::from("SOUND"))
+        .spawn(move || {
+            let mut player = Player::new(buzzer);
+            loop {
+                let sound = match player.poll(Instant::now()) {
+                    Some(next) => receiver.recv_timeout(next.saturating_duration_since(Instant::now())),
+                    None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match sound {
+                    Ok(Sound::Play(notes, repeat)) => player.play(notes, repeat, Instant::now()),
+                    Ok(Sound::Stop) => player.stop(),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        })?;
+    SOUND.lock().unwrap().replace(sender);
+    Ok(())
+}
+
+fn send(sound: Sound) {
+    if let Some(sender) = SOUND.lock().unwrap().as_ref() {
+        sender.send(sound).ok();
+    }
+}
+
+/// plays an RTTTL ringtone over whatever plays, `repeat` keeps it going until `stop`
+pub fn play(ringtone: &str, repeat: bool) {
+    match rtttl::parse(ringtone) {
+        Ok(notes) => send(Sound::Play(notes, repeat)),
+        Err(e) => error!("invalid ringtone {:?}: {}", ringtone, e),
+    }
+}
+
+pub fn stop() {
+    send(Sound::Stop);
+}
@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+/// durations offered in seconds, from a stand-up to a long build
+pub const PRESETS: [u64; 8] = [60, 3 * 60, 5 * 60, 10 * 60, 15 * 60, 25 * 60, 30 * 60, 60 * 60];
+
+enum State {
+    Idle,
+    Running { ends: Instant },
+    Paused { left: Duration },
+    /// ran out and rings until dismissed
+    Expired,
+}
+
+/// counts a preset down on the monotonic clock
+pub struct Countdown {
+    preset: usize,
+    state: State,
+}
+
+impl Default for Countdown {
+    fn default() -> Self {
+        Self { preset: 2, state: State::Idle }
+    }
+}
+
+impl Countdown {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(PRESETS[self.preset])
+    }
+
+    pub fn left(&self, now: Instant) -> Duration {
+        match self.state {
+            State::Idle => self.duration(),
+            State::Running { ends } => ends.saturating_duration_since(now),
+            State::Paused { left } => left,
+            State::Expired => Duration::ZERO,
+        }
+    }
+
+    /// share of the time still left, 1 before it starts
+    pub fn progress(&self, now: Instant) -> f32 {
+        self.left(now).as_secs_f32() / self.duration().as_secs_f32()
+    }
+
+    pub fn idle(&self) -> bool {
+        matches!(self.state, State::Idle)
+    }
+    pub fn running(&self) -> bool {
+        matches!(self.state, State::Running { .. })
+    }
+    pub fn ringing(&self) -> bool {
+        matches!(self.state, State::Expired)
+    }
+
+    pub fn start_pause(&mut self, now: Instant) {
+        self.state = match self.state {
+            State::Idle => State::Running { ends: now + self.duration() },
+            State::Running { ends } => State::Paused { left: ends.saturating_duration_since(now) },
+            State::Paused { left } => State::Running { ends: now + left },
+            State::Expired => State::Idle,
+        };
+    }
+
+    /// picks the next preset while idle, stops the countdown otherwise
+    pub fn next(&mut self) {
+        match self.state {
+            State::Idle => self.preset = (self.preset + 1) % PRESETS.len(),
+            _ => self.state = State::Idle,
+        }
+    }
+    pub fn prev(&mut self) {
+        match self.state {
+            State::Idle => self.preset = (self.preset + PRESETS.len() - 1) % PRESETS.len(),
+            _ => self.state = State::Idle,
+        }
+    }
+
+    /// true once, when it runs out at `now`
+    pub fn poll(&mut self, now: Instant) -> bool {
+        match self.state {
+            State::Running { ends } if ends <= now => {
+                self.state = State::Expired;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn dismiss(&mut self) {
+        if self.ringing() {
+            self.state = State::Idle;
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+pub mod countdown;
+pub mod pomodoro;
+
+pub use clock_core::timer::stopwatch;
+
+/// `m:ss` below an hour and `h:mm:ss` above, `tenths` adds `.d`
+pub fn format_duration(duration: Duration, tenths: bool) -> String {
+    let seconds = duration.as_secs();
+    let time = match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    };
+    match tenths {
+        true => format!("{}.{}", time, duration.subsec_millis() / 100),
+        false => time,
+    }
+}
@@ -0,0 +1,191 @@
+use std::time::{Duration, Instant};
+
+use chrono::{Datelike, NaiveDate};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::fs::config::{CONFIG, PomodoroIntervals};
+use crate::fs::store;
+
+const SESSIONS_KEY: &str = "pomodoro";
+/// days of session counts kept
+const SESSION_DAYS: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Break",
+            Phase::LongBreak => "Long break",
+        }
+    }
+}
+
+enum State {
+    Ready,
+    Running { ends: Instant },
+    Paused { left: Duration },
+}
+
+/// finished work sessions of one day
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Sessions {
+    /// days since 0001-01-01, chrono is built without serde
+    day: i32,
+    count: u32,
+}
+
+/// work and break phases one after the other, each waits for Ok before it starts
+pub struct Pomodoro {
+    intervals: PomodoroIntervals,
+    phase: Phase,
+    /// work sessions finished since the last long break
+    streak: u32,
+    state: State,
+    ringing: bool,
+    /// newest first
+    sessions: Vec<Sessions>,
+}
+
+impl Pomodoro {
+    pub fn new(intervals: PomodoroIntervals) -> Self {
+        Self {
+            intervals,
+            phase: Phase::Work,
+            streak: 0,
+            state: State::Ready,
+            ringing: false,
+            sessions: store::load(SESSIONS_KEY).unwrap_or_default(),
+        }
+    }
+
+    /// intervals from the config, the session counts from flash
+    pub fn load() -> Self {
+        Self::new(CONFIG.as_ref().map_or(PomodoroIntervals::default(), |config| config.pomodoro))
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn duration(&self) -> Duration {
+        let minutes = match self.phase {
+            Phase::Work => self.intervals.work,
+            Phase::ShortBreak => self.intervals.short_break,
+            Phase::LongBreak => self.intervals.long_break,
+        };
+        Duration::from_secs(minutes.max(1) as u64 * 60)
+    }
+
+    pub fn left(&self, now: Instant) -> Duration {
+        match self.state {
+            State::Ready => self.duration(),
+            State::Running { ends } => ends.saturating_duration_since(now),
+            State::Paused { left } => left,
+        }
+    }
+
+    /// share of the phase still left
+    pub fn progress(&self, now: Instant) -> f32 {
+        self.left(now).as_secs_f32() / self.duration().as_secs_f32()
+    }
+
+    pub fn ready(&self) -> bool {
+        matches!(self.state, State::Ready)
+    }
+    pub fn running(&self) -> bool {
+        matches!(self.state, State::Running { .. })
+    }
+    pub fn ringing(&self) -> bool {
+        self.ringing
+    }
+
+    pub fn start_pause(&mut self, now: Instant) {
+        self.state = match self.state {
+            State::Ready => State::Running { ends: now + self.duration() },
+            State::Running { ends } => State::Paused { left: ends.saturating_duration_since(now) },
+            State::Paused { left } => State::Running { ends: now + left },
+        };
+    }
+
+    fn advance(&mut self, worked: bool) {
+        self.phase = match self.phase {
+            Phase::Work if worked => {
+                self.streak += 1;
+                match self.streak >= self.intervals.long_break_after.max(1) {
+                    true => {
+                        self.streak = 0;
+                        Phase::LongBreak
+                    }
+                    false => Phase::ShortBreak,
+                }
+            }
+            Phase::Work => Phase::ShortBreak,
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        };
+        self.state = State::Ready;
+    }
+
+    /// on to the next phase without counting this one
+    pub fn skip(&mut self) {
+        self.advance(false);
+    }
+
+    /// back to the start of a cycle
+    pub fn reset(&mut self) {
+        self.phase = Phase::Work;
+        self.streak = 0;
+        self.state = State::Ready;
+    }
+
+    /// true once, when the phase runs out at `now`, a work phase counts for `today`
+    pub fn poll(&mut self, now: Instant, today: NaiveDate) -> bool {
+        match self.state {
+            State::Running { ends } if ends <= now => {
+                let worked = self.phase == Phase::Work;
+                if worked {
+                    self.count(today);
+                }
+                self.advance(worked);
+                self.ringing = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn dismiss(&mut self) {
+        self.ringing = false;
+    }
+
+    fn count(&mut self, today: NaiveDate) {
+        let day = today.num_days_from_ce();
+        match self.sessions.first_mut() {
+            Some(sessions) if sessions.day == day => sessions.count += 1,
+            _ => self.sessions.insert(0, Sessions { day, count: 1 }),
+        }
+        self.sessions.truncate(SESSION_DAYS);
+        if let Err(e) = store::save(SESSIONS_KEY, &self.sessions) {
+            error!("save pomodoro sessions failed: {}", e);
+        }
+    }
+
+    /// work sessions finished on `day`
+    pub fn sessions(&self, day: NaiveDate) -> u32 {
+        let day = day.num_days_from_ce();
+        self.sessions.iter().find(|sessions| sessions.day == day).map_or(0, |sessions| sessions.count)
+    }
+
+    /// work sessions finished in the week up to `day`
+    pub fn week(&self, day: NaiveDate) -> u32 {
+        let day = day.num_days_from_ce();
+        self.sessions.iter().filter(|sessions| (day - 6..=day).contains(&sessions.day)).map(|sessions| sessions.count).sum()
+    }
+}
@@ -0,0 +1,36 @@
+use std::ops::Deref;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use lazy_static::lazy_static;
+use log::error;
+
+use crate::fs::config::CONFIG;
+
+pub mod world;
+
+pub use clock_core::tz::{posix, zones, Zone};
+
+lazy_static!{
+    /// `time_zone` from the config, `date_fixed_offset` when it is missing
+    pub static ref LOCAL: Zone = match CONFIG.deref() {
+        None => Zone::default(),
+        Some(config) => match config.time_zone.as_deref() {
+            None => Zone::fixed(config.date_fixed_offset),
+            Some(name) => Zone::parse(name).unwrap_or_else(|| {
+                error!("unknown time zone: {}", name);
+                Zone::fixed(config.date_fixed_offset)
+            }),
+        },
+    };
+}
+
+/// `utc` in the configured zone
+pub fn local(utc: &DateTime<Utc>) -> DateTime<FixedOffset> {
+    LOCAL.to_local(utc)
+}
+
+/// `utc` formatted in the configured zone, `%Z` is the zone abbreviation instead of chrono's offset
+pub fn format(utc: &DateTime<Utc>, fmt: &str) -> String {
+    let fmt = fmt.replace("%Z", LOCAL.abbreviation(utc));
+    format!("{}", local(utc).format(&fmt))
+}
@@ -0,0 +1,69 @@
+use std::ops::Deref;
+
+use chrono::{DateTime, FixedOffset, Timelike, Utc};
+use lazy_static::lazy_static;
+use log::error;
+
+use crate::fs::config::CONFIG;
+use crate::tz::Zone;
+
+/// local hours counted as day for the day/night marker
+const DAY_START: u32 = 6;
+const DAY_END: u32 = 18;
+
+pub struct City {
+    pub name: String,
+    pub zone: Zone,
+}
+
+/// one row of the world clock page
+#[derive(Debug, Clone)]
+pub struct CityTime {
+    pub name: String,
+    pub local: DateTime<FixedOffset>,
+    pub abbreviation: String,
+}
+
+impl CityTime {
+    pub fn is_day(&self) -> bool {
+        (DAY_START..DAY_END).contains(&self.local.hour())
+    }
+    /// `UTC+8`, `UTC-3:30`
+    pub fn utc_offset(&self) -> String {
+        let seconds = self.local.offset().local_minus_utc();
+        let sign = if seconds < 0 { '-' } else { '+' };
+        let (hours, minutes) = (seconds.abs() / 3600, seconds.abs() % 3600 / 60);
+        match minutes {
+            0 => format!("UTC{}{}", sign, hours),
+            _ => format!("UTC{}{}:{:02}", sign, hours, minutes),
+        }
+    }
+}
+
+lazy_static!{
+    /// `world_clocks` from the config, entries with an unknown zone are left out
+    pub static ref CITIES: Vec<City> = match CONFIG.deref() {
+        None => Vec::new(),
+        Some(config) => config.world_clocks
+            .iter()
+            .filter_map(|city| match Zone::parse(&city.time_zone) {
+                Some(zone) => Some(City { name: city.name.clone(), zone }),
+                None => {
+                    error!("unknown time zone for {}: {}", city.name, city.time_zone);
+                    None
+                }
+            })
+            .collect(),
+    };
+}
+
+pub fn city_times(utc: &DateTime<Utc>) -> Vec<CityTime> {
+    CITIES
+        .iter()
+        .map(|city| CityTime {
+            name: city.name.clone(),
+            local: city.zone.to_local(utc),
+            abbreviation: city.zone.abbreviation(utc).into(),
+        })
+        .collect()
+}
@@ -3,6 +3,7 @@ use std::fmt::{Display, Formatter};
 use esp_idf_hal::sys::heap_caps_get_info;
 use esp_idf_svc::sys::{esp, esp_efuse_mac_get_default, MALLOC_CAP_DEFAULT, multi_heap_info_t};
 
+pub mod pins;
 pub mod state;
 
 #[repr(transparent)]
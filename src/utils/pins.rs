@@ -0,0 +1,27 @@
+use std::sync::Mutex;
+
+use esp_idf_hal::gpio::AnyIOPin;
+use lazy_static::lazy_static;
+
+/// the ESP32-C3 has GPIO 0 to 21
+const MAX_PIN: i32 = 21;
+
+lazy_static!{
+    /// pins in use, the display on 0 and 2 to 5 and the buttons on 8 and 10 as wired in `main`,
+    /// 11 to 17 for the flash and 18, 19 for the usb port it is flashed and logs through
+    static ref CLAIMED: Mutex<Vec<i32>> = Mutex::new(vec![0, 2, 3, 4, 5, 8, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19]);
+}
+
+/// a GPIO number from the config, refused when it does not exist or something else is wired to it
+pub fn claim(pin: i32) -> anyhow::Result<AnyIOPin> {
+    let mut claimed = CLAIMED.lock().unwrap();
+    if !(0..=MAX_PIN).contains(&pin) {
+        anyhow::bail!("GPIO {} does not exist", pin);
+    }
+    if claimed.contains(&pin) {
+        anyhow::bail!("GPIO {} is already in use", pin);
+    }
+    claimed.push(pin);
+    // checked above that nothing else drives it
+    Ok(unsafe { AnyIOPin::new(pin) })
+}